@@ -19,13 +19,13 @@ fn main() {
         a_seq: Vec<String>,
     }
 
-    let foo = Foo::deserialize(de).unwrap();
-    debug_assert_eq!(foo.a_string, "BAR");
-    debug_assert_eq!(foo.escaped_string, "q w e");
-    debug_assert_eq!(foo.an_int, 42);
-    debug_assert_eq!(foo.a_float, 42.1);
+    let value = Foo::deserialize(de).unwrap();
+    debug_assert_eq!(value.a_string, "BAR");
+    debug_assert_eq!(value.escaped_string, "q w e");
+    debug_assert_eq!(value.an_int, 42);
+    debug_assert_eq!(value.a_float, 42.1);
     debug_assert_eq!(
-        foo.a_seq,
+        value.a_seq,
         vec!["a".to_owned(), "b".to_owned(), "a w e".to_owned()]
     );
 }
@@ -1,15 +1,310 @@
+use alloc::{borrow::Cow, format};
+
 use base64::Engine;
 use serde::de::{EnumAccess, MapAccess, SeqAccess, VariantAccess, Visitor};
 
 use crate::{
-    parser::Parser,
-    unescape::{unescape, unescaped},
-    Error, ValueDeserializer,
+    de::ValueDeserializer,
+    parser::{parse_quoted_str, Parser},
+    unescape::{unescape_with, unescaped_with, EscapeError},
+    Error, ErrorKind,
 };
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct BasicParser;
 
+impl BasicParser {
+    /// Parses comma-separated sequence,
+    /// using `parser` for elements.
+    ///
+    /// Allows custom parsers to reuse sequence syntax of the basic parser.
+    pub fn parse_seq_with<'de, P, V>(parser: P, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        P: Parser,
+        V: Visitor<'de>,
+    {
+        DelimitedParser::new().parse_seq_with(parser, value, visitor)
+    }
+
+    /// Parses comma-separated key:value pairs,
+    /// using `parser` for keys and values.
+    ///
+    /// Allows custom parsers to reuse map syntax of the basic parser.
+    pub fn parse_map_with<'de, P, V>(parser: P, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        P: Parser,
+        V: Visitor<'de>,
+    {
+        DelimitedParser::new().parse_map_with(parser, value, visitor)
+    }
+
+    /// Parses `variant:value` enum,
+    /// using `parser` for variant name and its content.
+    ///
+    /// Allows custom parsers to reuse enum syntax of the basic parser.
+    pub fn parse_enum_with<'de, P, V>(parser: P, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        P: Parser,
+        V: Visitor<'de>,
+    {
+        DelimitedParser::new().parse_enum_with(parser, value, visitor)
+    }
+}
+
+/// Basic parser with configurable delimiters and quote character.
+///
+/// Default configuration is the same as [`BasicParser`]:
+/// elements are separated by `,`, keys and values by `:`,
+/// and values may be quoted with `"`.
+/// Separators may consist of several characters.
+///
+/// Nested sequences and maps are enclosed in brackets, e.g. `[1, 2], [3]`.
+/// Separators inside brackets are not split on.
+/// Outermost brackets are optional, so string element `[a]` must be quoted.
+/// Unbalanced brackets, as in `a[b, c`, are plain characters.
+#[derive(Clone, Copy, Debug)]
+pub struct DelimitedParser {
+    separator: &'static str,
+    key_separator: &'static str,
+    quote: char,
+}
+
+impl DelimitedParser {
+    pub const fn new() -> Self {
+        DelimitedParser {
+            separator: ",",
+            key_separator: ":",
+            quote: '"',
+        }
+    }
+
+    /// Sets separator between elements of sequences and entries of maps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `separator` is empty.
+    pub const fn separator(mut self, separator: &'static str) -> Self {
+        assert!(!separator.is_empty(), "separator must not be empty");
+        self.separator = separator;
+        self
+    }
+
+    /// Sets separator between key and value of map entries
+    /// and between enum variant and its value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `separator` is empty.
+    pub const fn key_separator(mut self, separator: &'static str) -> Self {
+        assert!(!separator.is_empty(), "key separator must not be empty");
+        self.key_separator = separator;
+        self
+    }
+
+    /// Sets character that opens and closes escaped strings.
+    pub const fn quote(mut self, quote: char) -> Self {
+        self.quote = quote;
+        self
+    }
+
+    /// Parses sequence with this syntax,
+    /// using `parser` for elements.
+    pub fn parse_seq_with<'de, P, V>(
+        self,
+        parser: P,
+        value: &str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        P: Parser,
+        V: Visitor<'de>,
+    {
+        visitor.visit_seq(BasicSeqAccess {
+            value: self.strip_brackets(value),
+            parser,
+            syntax: self,
+        })
+    }
+
+    /// Parses map with this syntax,
+    /// using `parser` for keys and values.
+    pub fn parse_map_with<'de, P, V>(
+        self,
+        parser: P,
+        value: &str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        P: Parser,
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(BasicMapAccess {
+            value: self.strip_brackets(value),
+            parser,
+            syntax: self,
+        })
+    }
+
+    /// Parses enum with this syntax,
+    /// using `parser` for variant name and its content.
+    pub fn parse_enum_with<'de, P, V>(
+        self,
+        parser: P,
+        value: &str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        P: Parser,
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(BasicEnumAccess {
+            value,
+            parser,
+            syntax: self,
+        })
+    }
+
+    /// Splits `s` at the first `pattern` outside of brackets.
+    fn split_once<'a>(self, s: &'a str, pattern: &str) -> Option<(&'a str, &'a str)> {
+        let index = self.find(s, pattern)?;
+        Some((&s[..index], &s[index + pattern.len()..]))
+    }
+
+    /// Finds the first `pattern` outside of brackets.
+    fn find(self, s: &str, pattern: &str) -> Option<usize> {
+        self.scan(s, |index, depth| {
+            depth == 0 && s[index..].starts_with(pattern)
+        })
+    }
+
+    /// Walks through `s` tracking depth of brackets
+    /// and returns first index where `stop` returns `true`.
+    /// Quoted strings are skipped.
+    ///
+    /// Brackets nest only if they are balanced,
+    /// otherwise they are treated as plain characters.
+    fn scan(self, s: &str, mut stop: impl FnMut(usize, usize) -> bool) -> Option<usize> {
+        let nested = self.balanced(s);
+        self.walk(s, nested, |index, _, depth| stop(index, depth))
+    }
+
+    /// Checks that every bracket outside of quoted strings has a pair.
+    fn balanced(self, s: &str) -> bool {
+        let mut depth = 0usize;
+        let unpaired = self.walk(s, false, |_, c, _| match c {
+            '[' => {
+                depth += 1;
+                false
+            }
+            ']' => match depth.checked_sub(1) {
+                Some(outer) => {
+                    depth = outer;
+                    false
+                }
+                None => true,
+            },
+            _ => false,
+        });
+        unpaired.is_none() && depth == 0
+    }
+
+    /// Calls `f` with index, character and depth of brackets
+    /// for characters outside of quoted strings, until it returns `true`.
+    /// Depth is not tracked unless `nested` is `true`.
+    ///
+    /// Quoted string starts only at the start of an element,
+    /// so quotes inside unquoted elements are plain characters.
+    fn walk(
+        self,
+        s: &str,
+        nested: bool,
+        mut f: impl FnMut(usize, char, usize) -> bool,
+    ) -> Option<usize> {
+        let mut depth = 0usize;
+        let mut quoted = false;
+        let mut escaped = false;
+        let mut element = 0;
+
+        for (index, c) in s.char_indices() {
+            if quoted {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == self.quote {
+                    quoted = false;
+                }
+                continue;
+            }
+            if f(index, c, depth) {
+                return Some(index);
+            }
+            let rest = &s[index..];
+            match c {
+                '[' if nested => {
+                    depth += 1;
+                    element = index + 1;
+                }
+                ']' => depth = depth.saturating_sub(1),
+                c if c == self.quote && s[element..index].trim().is_empty() => quoted = true,
+                _ if rest.starts_with(self.separator) => element = index + self.separator.len(),
+                _ if rest.starts_with(self.key_separator) => {
+                    element = index + self.key_separator.len()
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+
+    /// Removes brackets that enclose whole value.
+    fn strip_brackets(self, value: &str) -> &str {
+        let trimmed = value.trim();
+        if !trimmed.starts_with('[') {
+            return value;
+        }
+
+        // Brackets must match each other, as in `[1, 2]` but not `[1], [2]`.
+        let close = self.scan(trimmed, |index, depth| {
+            depth == 1 && trimmed[index..].starts_with(']')
+        });
+        match close {
+            Some(index) if index == trimmed.len() - 1 => &trimmed[1..index],
+            _ => value,
+        }
+    }
+
+    fn unescaped(self, value: &str) -> Result<Cow<'_, str>, EscapeError> {
+        unescaped_with(value.trim(), self.quote)
+    }
+
+    fn invalid_seq(self, s: &str) -> Error {
+        serde::de::Error::invalid_value(
+            serde::de::Unexpected::Str(s),
+            &&*format!(
+                "a potentially escaped strings delimited by `{}`",
+                self.separator
+            ),
+        )
+    }
+
+    fn invalid_map(self, s: &str) -> Error {
+        serde::de::Error::invalid_value(
+            serde::de::Unexpected::Str(s),
+            &&*format!(
+                "a potentially escaped key{}value pairs delimited by `{}`",
+                self.key_separator, self.separator
+            ),
+        )
+    }
+}
+
+impl Default for DelimitedParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 macro_rules! impl_num_from_str {
     ($($parse:ident $type:ident $visit:ident)*) => {
         $(
@@ -18,14 +313,15 @@ macro_rules! impl_num_from_str {
             where
                 V: Visitor<'de>,
             {
-                let with_err = || {
+                let with_err = || -> Error {
                     serde::de::Error::invalid_value(
                         serde::de::Unexpected::Str(value),
                         &"a potentially escaped string with number",
                     )
                 };
 
-                let value = unescaped(value.trim()).map_err(|_| with_err())?;
+                let value = self.unescaped(value)
+                    .map_err(|_| with_err().with_kind(ErrorKind::InvalidEscape))?;
                 let value = value
                     .parse::<$type>()
                     .map_err(|_| with_err())?;
@@ -35,20 +331,22 @@ macro_rules! impl_num_from_str {
     };
 }
 
-impl Parser for BasicParser {
+impl Parser for DelimitedParser {
     #[inline]
     fn parse_bool<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        let with_err = || {
+        let with_err = || -> Error {
             serde::de::Error::invalid_value(
                 serde::de::Unexpected::Str(value),
                 &"a potentially escaped string with boolean",
             )
         };
 
-        let value = unescaped(value.trim()).map_err(|_| with_err())?;
+        let value = self
+            .unescaped(value)
+            .map_err(|_| with_err().with_kind(ErrorKind::InvalidEscape))?;
 
         match &*value.to_lowercase() {
             "true" | "1" | "+" | "y" | "yea" | "yes" | "yeah" | "yah" | "aye" => {
@@ -56,7 +354,7 @@ impl Parser for BasicParser {
             }
             "false" | "0" | "-" | "n" | "nay" | "no" | "nah" => visitor.visit_bool(false),
             _ => Err(serde::de::Error::invalid_value(
-                serde::de::Unexpected::Str(&*value),
+                serde::de::Unexpected::Str(&value),
                 &"one of: true, false, 1, 0, +, -, y, n, ye, ya, yea, yeah, yah, aye, nay, no, nah",
             )),
         }
@@ -81,14 +379,14 @@ impl Parser for BasicParser {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_seq(BasicSeqAccess { value })
+        self.parse_seq_with(self, value, visitor)
     }
 
     fn parse_map<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        visitor.visit_map(BasicMapAccess { value })
+        self.parse_map_with(self, value, visitor)
     }
 
     fn parse_struct<'de, V>(
@@ -101,7 +399,7 @@ impl Parser for BasicParser {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_map(BasicMapAccess { value })
+        self.parse_map_with(self, value, visitor)
     }
 
     fn parse_enum<'de, V>(
@@ -114,24 +412,26 @@ impl Parser for BasicParser {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_enum(BasicEnumAccess { value })
+        self.parse_enum_with(self, value, visitor)
     }
 
     fn parse_bytes<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>,
     {
-        let with_err = || {
+        let with_err = || -> Error {
             serde::de::Error::invalid_value(
                 serde::de::Unexpected::Str(value),
                 &"a potentially escaped string with base64 sequence",
             )
         };
-        let value = unescaped(value.trim()).map_err(|_| with_err())?;
+        let value = self
+            .unescaped(value)
+            .map_err(|_| with_err().with_kind(ErrorKind::InvalidEscape))?;
 
         let decoded = base64::engine::general_purpose::STANDARD_NO_PAD
             .decode(&*value)
-            .map_err(|_| with_err())?;
+            .map_err(|_| with_err().with_kind(ErrorKind::InvalidBase64))?;
         visitor.visit_byte_buf(decoded)
     }
 
@@ -139,81 +439,159 @@ impl Parser for BasicParser {
     where
         V: Visitor<'de>,
     {
-        let with_err = || {
+        let with_err = || -> Error {
             serde::de::Error::invalid_value(
                 serde::de::Unexpected::Str(value),
                 &"a potentially escaped string value",
             )
         };
 
-        let value = unescaped(value.trim()).map_err(|_| with_err())?;
-        visitor.visit_str(&*value)
+        let value = self
+            .unescaped(value)
+            .map_err(|_| with_err().with_kind(ErrorKind::InvalidEscape))?;
+        visitor.visit_str(&value)
+    }
+
+    fn parse_str<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        parse_quoted_str(value, self.quote, visitor)
+    }
+
+    fn parse_borrowed_str<'de, V>(self, value: &'de str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if value.starts_with(self.quote) {
+            self.parse_str(value, visitor)
+        } else {
+            visitor.visit_borrowed_str(value)
+        }
     }
 }
 
-struct BasicSeqAccess<'a> {
+macro_rules! delegate_to_delimited {
+    ($($parse:ident)*) => {
+        $(
+            #[inline]
+            fn $parse<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                DelimitedParser::new().$parse(value, visitor)
+            }
+        )*
+    };
+}
+
+impl Parser for BasicParser {
+    delegate_to_delimited! {
+        parse_bool
+        parse_i8 parse_i16 parse_i32 parse_i64 parse_i128
+        parse_u8 parse_u16 parse_u32 parse_u64 parse_u128
+        parse_f32 parse_f64 parse_bytes parse_any
+    }
+
+    fn parse_seq<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        BasicParser::parse_seq_with(self, value, visitor)
+    }
+
+    fn parse_map<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        BasicParser::parse_map_with(self, value, visitor)
+    }
+
+    fn parse_struct<'de, V>(
+        self,
+        value: &str,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        BasicParser::parse_map_with(self, value, visitor)
+    }
+
+    fn parse_enum<'de, V>(
+        self,
+        value: &str,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        BasicParser::parse_enum_with(self, value, visitor)
+    }
+}
+
+struct BasicSeqAccess<'a, P> {
     value: &'a str,
+    parser: P,
+    syntax: DelimitedParser,
 }
 
-fn invalid_comma_separated_seq<E>(s: &str) -> E
+impl<'de, 'a, P> SeqAccess<'de> for BasicSeqAccess<'a, P>
 where
-    E: serde::de::Error,
+    P: Parser,
 {
-    serde::de::Error::invalid_value(
-        serde::de::Unexpected::Str(s),
-        &"a potentially escaped strings delimited by comma",
-    )
-}
-
-impl<'de, 'a> SeqAccess<'de> for BasicSeqAccess<'a> {
     type Error = Error;
 
     fn next_element_seed<U>(&mut self, seed: U) -> Result<Option<U::Value>, Error>
     where
         U: serde::de::DeserializeSeed<'de>,
     {
+        let syntax = self.syntax;
+
         self.value = self.value.trim_start();
         if self.value.is_empty() {
             return Ok(None);
         }
-        match self.value.strip_prefix('"') {
-            None => match self.value.split_once(',') {
+        match self.value.strip_prefix(syntax.quote) {
+            None => match syntax.split_once(self.value, syntax.separator) {
                 None => {
                     let value = core::mem::take(&mut self.value).trim_end();
-                    seed.deserialize(ValueDeserializer {
-                        value,
-                        parser: BasicParser,
-                    })
-                    .map(Some)
+                    seed.deserialize(ValueDeserializer::new(value, self.parser))
+                        .map(Some)
                 }
                 Some((head, tail)) => {
                     self.value = tail;
-                    seed.deserialize(ValueDeserializer {
-                        value: head.trim_end(),
-                        parser: BasicParser,
-                    })
-                    .map(Some)
+                    seed.deserialize(ValueDeserializer::new(head.trim_end(), self.parser))
+                        .map(Some)
                 }
             },
             Some(escaped) => {
-                let (unescaped, tail) =
-                    unescape(escaped).map_err(|_| invalid_comma_separated_seq(self.value))?;
-
-                let next = seed.deserialize(ValueDeserializer {
-                    value: &unescaped,
-                    parser: BasicParser,
+                let (unescaped, tail) = unescape_with(escaped, syntax.quote).map_err(|_| {
+                    syntax
+                        .invalid_seq(self.value)
+                        .with_kind(ErrorKind::InvalidEscape)
                 })?;
 
+                let next = seed.deserialize(ValueDeserializer::new(&unescaped, self.parser))?;
+
                 match tail {
-                    None => return Err(invalid_comma_separated_seq(self.value)),
+                    None => {
+                        return Err(syntax
+                            .invalid_seq(self.value)
+                            .with_kind(ErrorKind::InvalidEscape))
+                    }
                     Some(tail) => {
                         let tail = tail.trim_start();
                         if tail.is_empty() {
                             self.value = tail;
                             return Ok(Some(next));
                         }
-                        match tail.strip_prefix(',') {
-                            None => return Err(invalid_comma_separated_seq(self.value)),
+                        match tail.strip_prefix(syntax.separator) {
+                            None => return Err(syntax.invalid_seq(self.value)),
                             Some(tail) => {
                                 self.value = tail;
                             }
@@ -227,65 +605,60 @@ impl<'de, 'a> SeqAccess<'de> for BasicSeqAccess<'a> {
     }
 }
 
-struct BasicMapAccess<'a> {
+struct BasicMapAccess<'a, P> {
     value: &'a str,
+    parser: P,
+    syntax: DelimitedParser,
 }
 
-fn invalid_comma_colon_separated_seq<E>(s: &str) -> E
+impl<'de, 'a, P> MapAccess<'de> for BasicMapAccess<'a, P>
 where
-    E: serde::de::Error,
+    P: Parser,
 {
-    serde::de::Error::invalid_value(
-        serde::de::Unexpected::Str(s),
-        &"a potentially escaped key:value pairs delimited by comma",
-    )
-}
-
-impl<'de, 'a> MapAccess<'de> for BasicMapAccess<'a> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
     where
         K: serde::de::DeserializeSeed<'de>,
     {
+        let syntax = self.syntax;
+
         self.value = self.value.trim_start();
         if self.value.is_empty() {
             return Ok(None);
         }
-        match self.value.strip_prefix('"') {
-            None => match self.value.split_once(':') {
+        match self.value.strip_prefix(syntax.quote) {
+            None => match syntax.split_once(self.value, syntax.key_separator) {
                 None => {
                     let value = core::mem::take(&mut self.value).trim_end();
-                    seed.deserialize(ValueDeserializer {
-                        value,
-                        parser: BasicParser,
-                    })
-                    .map(Some)
+                    seed.deserialize(ValueDeserializer::new(value, self.parser))
+                        .map(Some)
                 }
                 Some((head, tail)) => {
                     self.value = tail;
-                    seed.deserialize(ValueDeserializer {
-                        value: head.trim_end(),
-                        parser: BasicParser,
-                    })
-                    .map(Some)
+                    seed.deserialize(ValueDeserializer::new(head.trim_end(), self.parser))
+                        .map(Some)
                 }
             },
             Some(escaped) => {
-                let (unescaped, tail) =
-                    unescape(escaped).map_err(|_| invalid_comma_colon_separated_seq(self.value))?;
-
-                let next = seed.deserialize(ValueDeserializer {
-                    value: &unescaped,
-                    parser: BasicParser,
+                let (unescaped, tail) = unescape_with(escaped, syntax.quote).map_err(|_| {
+                    syntax
+                        .invalid_map(self.value)
+                        .with_kind(ErrorKind::InvalidEscape)
                 })?;
 
+                let next = seed.deserialize(ValueDeserializer::new(&unescaped, self.parser))?;
+
                 match tail {
-                    None => return Err(invalid_comma_colon_separated_seq(self.value)),
+                    None => {
+                        return Err(syntax
+                            .invalid_map(self.value)
+                            .with_kind(ErrorKind::InvalidEscape))
+                    }
                     Some(tail) => {
                         let tail = tail.trim_start();
-                        match tail.strip_prefix(':') {
-                            None => return Err(invalid_comma_colon_separated_seq(self.value)),
+                        match tail.strip_prefix(syntax.key_separator) {
+                            None => return Err(syntax.invalid_map(self.value)),
                             Some(tail) => {
                                 self.value = tail;
                             }
@@ -302,39 +675,41 @@ impl<'de, 'a> MapAccess<'de> for BasicMapAccess<'a> {
     where
         U: serde::de::DeserializeSeed<'de>,
     {
+        let syntax = self.syntax;
+
         self.value = self.value.trim_start();
-        match self.value.strip_prefix('"') {
-            None => match self.value.split_once(',') {
+        match self.value.strip_prefix(syntax.quote) {
+            None => match syntax.split_once(self.value, syntax.separator) {
                 None => {
                     let value = core::mem::take(&mut self.value).trim_end();
-                    seed.deserialize(ValueDeserializer {
-                        value,
-                        parser: BasicParser,
-                    })
+                    seed.deserialize(ValueDeserializer::new(value, self.parser))
                 }
                 Some((head, tail)) => {
                     self.value = tail;
-                    seed.deserialize(ValueDeserializer {
-                        value: head.trim_end(),
-                        parser: BasicParser,
-                    })
+                    seed.deserialize(ValueDeserializer::new(head.trim_end(), self.parser))
                 }
             },
             Some(escaped) => {
-                let (unescaped, tail) =
-                    unescape(escaped).map_err(|_| invalid_comma_colon_separated_seq(self.value))?;
-
-                let next = seed.deserialize(ValueDeserializer {
-                    value: &unescaped,
-                    parser: BasicParser,
+                let (unescaped, tail) = unescape_with(escaped, syntax.quote).map_err(|_| {
+                    syntax
+                        .invalid_map(self.value)
+                        .with_kind(ErrorKind::InvalidEscape)
                 })?;
 
+                let next = seed.deserialize(ValueDeserializer::new(&unescaped, self.parser))?;
+
                 match tail {
-                    None => Err(invalid_comma_colon_separated_seq(self.value)),
+                    None => Err(syntax
+                        .invalid_map(self.value)
+                        .with_kind(ErrorKind::InvalidEscape)),
                     Some(tail) => {
                         let tail = tail.trim_start();
-                        match tail.strip_prefix(',') {
-                            None => Err(invalid_comma_colon_separated_seq(self.value)),
+                        if tail.is_empty() {
+                            self.value = tail;
+                            return Ok(next);
+                        }
+                        match tail.strip_prefix(syntax.separator) {
+                            None => Err(syntax.invalid_map(self.value)),
                             Some(tail) => {
                                 self.value = tail;
                                 Ok(next)
@@ -347,58 +722,79 @@ impl<'de, 'a> MapAccess<'de> for BasicMapAccess<'a> {
     }
 }
 
-struct BasicEnumAccess<'a> {
+struct BasicEnumAccess<'a, P> {
     value: &'a str,
+    parser: P,
+    syntax: DelimitedParser,
 }
 
-impl<'de, 'a> EnumAccess<'de> for BasicEnumAccess<'a> {
+impl<'de, 'a, P> EnumAccess<'de> for BasicEnumAccess<'a, P>
+where
+    P: Parser,
+{
     type Error = Error;
-    type Variant = BasicVariantAccess<'a>;
+    type Variant = BasicVariantAccess<'a, P>;
 
     fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self::Variant), Error>
     where
         V: serde::de::DeserializeSeed<'de>,
     {
+        let syntax = self.syntax;
+
         self.value = self.value.trim_start();
         if self.value.is_empty() {
             return Err(serde::de::Error::invalid_value(
                 serde::de::Unexpected::Str(self.value),
-                &"a potentially escaped key:value pair",
+                &&*format!(
+                    "a potentially escaped key{}value pair",
+                    syntax.key_separator
+                ),
             ));
         }
-        match self.value.strip_prefix('"') {
-            None => match self.value.split_once(':') {
+        match self.value.strip_prefix(syntax.quote) {
+            None => match syntax.split_once(self.value, syntax.key_separator) {
                 None => Ok((
-                    seed.deserialize(ValueDeserializer {
-                        value: self.value.trim_end(),
-                        parser: BasicParser,
-                    })?,
-                    BasicVariantAccess { value: "" },
+                    seed.deserialize(ValueDeserializer::new(self.value.trim_end(), self.parser))?,
+                    BasicVariantAccess {
+                        value: "",
+                        parser: self.parser,
+                        syntax,
+                    },
                 )),
                 Some((head, tail)) => Ok((
-                    seed.deserialize(ValueDeserializer {
-                        value: head.trim_end(),
-                        parser: BasicParser,
-                    })?,
-                    BasicVariantAccess { value: tail },
+                    seed.deserialize(ValueDeserializer::new(head.trim_end(), self.parser))?,
+                    BasicVariantAccess {
+                        value: tail,
+                        parser: self.parser,
+                        syntax,
+                    },
                 )),
             },
             Some(escaped) => {
-                let (unescaped, tail) =
-                    unescape(escaped).map_err(|_| invalid_comma_colon_separated_seq(self.value))?;
-
-                let variant = seed.deserialize(ValueDeserializer {
-                    value: &unescaped,
-                    parser: BasicParser,
+                let (unescaped, tail) = unescape_with(escaped, syntax.quote).map_err(|_| {
+                    syntax
+                        .invalid_map(self.value)
+                        .with_kind(ErrorKind::InvalidEscape)
                 })?;
 
+                let variant = seed.deserialize(ValueDeserializer::new(&unescaped, self.parser))?;
+
                 match tail {
-                    None => Err(invalid_comma_colon_separated_seq(self.value)),
+                    None => Err(syntax
+                        .invalid_map(self.value)
+                        .with_kind(ErrorKind::InvalidEscape)),
                     Some(tail) => {
                         let tail = tail.trim_start();
-                        match tail.strip_prefix(':') {
-                            None => Err(invalid_comma_colon_separated_seq(self.value)),
-                            Some(tail) => Ok((variant, BasicVariantAccess { value: tail })),
+                        match tail.strip_prefix(syntax.key_separator) {
+                            None => Err(syntax.invalid_map(self.value)),
+                            Some(tail) => Ok((
+                                variant,
+                                BasicVariantAccess {
+                                    value: tail,
+                                    parser: self.parser,
+                                    syntax,
+                                },
+                            )),
                         }
                     }
                 }
@@ -407,11 +803,16 @@ impl<'de, 'a> EnumAccess<'de> for BasicEnumAccess<'a> {
     }
 }
 
-struct BasicVariantAccess<'a> {
+struct BasicVariantAccess<'a, P> {
     value: &'a str,
+    parser: P,
+    syntax: DelimitedParser,
 }
 
-impl<'de, 'a> VariantAccess<'de> for BasicVariantAccess<'a> {
+impl<'de, 'a, P> VariantAccess<'de> for BasicVariantAccess<'a, P>
+where
+    P: Parser,
+{
     type Error = Error;
 
     fn unit_variant(self) -> Result<(), Error> {
@@ -422,17 +823,14 @@ impl<'de, 'a> VariantAccess<'de> for BasicVariantAccess<'a> {
     where
         T: serde::de::DeserializeSeed<'de>,
     {
-        seed.deserialize(ValueDeserializer {
-            value: self.value,
-            parser: BasicParser,
-        })
+        seed.deserialize(ValueDeserializer::new(self.value, self.parser))
     }
 
     fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value, Self::Error>
     where
         V: Visitor<'de>,
     {
-        BasicParser.parse_seq(self.value, visitor)
+        self.syntax.parse_seq_with(self.parser, self.value, visitor)
     }
 
     fn struct_variant<V>(
@@ -443,6 +841,307 @@ impl<'de, 'a> VariantAccess<'de> for BasicVariantAccess<'a> {
     where
         V: Visitor<'de>,
     {
-        visitor.visit_map(BasicMapAccess { value: self.value })
+        self.syntax.parse_map_with(self.parser, self.value, visitor)
+    }
+}
+
+#[test]
+fn test_custom_parser_elements() {
+    use alloc::vec::Vec;
+    use serde::Deserialize;
+
+    /// Accepts `on` and `off` as booleans.
+    #[derive(Clone, Copy)]
+    struct OnOffParser;
+
+    macro_rules! delegate {
+        ($($parse:ident)*) => {$(
+            fn $parse<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                BasicParser.$parse(value, visitor)
+            }
+        )*};
+    }
+
+    impl Parser for OnOffParser {
+        fn parse_bool<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            match value.trim() {
+                "on" => visitor.visit_bool(true),
+                "off" => visitor.visit_bool(false),
+                _ => BasicParser.parse_bool(value, visitor),
+            }
+        }
+
+        delegate! {
+            parse_i8 parse_i16 parse_i32 parse_i64 parse_i128
+            parse_u8 parse_u16 parse_u32 parse_u64 parse_u128
+            parse_f32 parse_f64 parse_bytes parse_any
+        }
+
+        fn parse_seq<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            BasicParser::parse_seq_with(self, value, visitor)
+        }
+
+        fn parse_map<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            BasicParser::parse_map_with(self, value, visitor)
+        }
+
+        fn parse_struct<'de, V>(
+            self,
+            value: &str,
+            _name: &'static str,
+            _fields: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            BasicParser::parse_map_with(self, value, visitor)
+        }
+
+        fn parse_enum<'de, V>(
+            self,
+            value: &str,
+            _name: &'static str,
+            _variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            BasicParser::parse_enum_with(self, value, visitor)
+        }
+    }
+
+    #[derive(serde_derive::Deserialize)]
+    struct Config {
+        flags: Vec<bool>,
+    }
+
+    let de = crate::Deserializer::from_vars([("FLAGS".into(), "on, off, yes".into())])
+        .with_options(crate::Options::new(OnOffParser));
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.flags, [true, false, true]);
+}
+
+#[test]
+fn test_custom_parser_tuple_variant() {
+    use alloc::string::String;
+    use serde::Deserialize;
+
+    /// Parses enums with `;` separated tuple variants.
+    #[derive(Clone, Copy)]
+    struct EnumParser;
+
+    macro_rules! delegate {
+        ($($parse:ident)*) => {$(
+            fn $parse<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                BasicParser.$parse(value, visitor)
+            }
+        )*};
+    }
+
+    impl Parser for EnumParser {
+        delegate! {
+            parse_bool parse_i8 parse_i16 parse_i32 parse_i64 parse_i128
+            parse_u8 parse_u16 parse_u32 parse_u64 parse_u128
+            parse_f32 parse_f64 parse_seq parse_map parse_bytes parse_any
+        }
+
+        fn parse_struct<'de, V>(
+            self,
+            value: &str,
+            name: &'static str,
+            fields: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            BasicParser.parse_struct(value, name, fields, visitor)
+        }
+
+        fn parse_enum<'de, V>(
+            self,
+            value: &str,
+            _name: &'static str,
+            _variants: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            DelimitedParser::new()
+                .separator(";")
+                .parse_enum_with(self, value, visitor)
+        }
+    }
+
+    #[derive(Debug, PartialEq, serde_derive::Deserialize)]
+    enum Range {
+        Between(u32, String),
+    }
+
+    #[derive(serde_derive::Deserialize)]
+    struct Config {
+        range: Range,
     }
+
+    let de = crate::Deserializer::from_str_vars([("RANGE", "Between:1;a,b")])
+        .with_options(crate::Options::new(EnumParser));
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.range, Range::Between(1, "a,b".into()));
+}
+
+#[test]
+fn test_map_quoted_last_value() {
+    use alloc::{collections::BTreeMap, string::String};
+    use serde::Deserialize;
+
+    #[derive(serde_derive::Deserialize)]
+    struct Config {
+        labels: BTreeMap<String, String>,
+    }
+
+    let de = crate::Deserializer::from_str_vars([("LABELS", "a:\"x,y\", b: \"z\" ")]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.labels["a"], "x,y");
+    assert_eq!(config.labels["b"], "z");
+}
+
+#[test]
+fn test_delimiters() {
+    use alloc::{string::String, vec::Vec};
+    use serde::Deserialize;
+
+    #[derive(serde_derive::Deserialize)]
+    struct Config {
+        urls: Vec<String>,
+        hosts: alloc::collections::BTreeMap<String, u16>,
+        labels: alloc::collections::BTreeMap<String, String>,
+        range: Range,
+    }
+
+    #[derive(Debug, PartialEq, serde_derive::Deserialize)]
+    enum Range {
+        Between(u32, String),
+    }
+
+    let de = crate::Deserializer::from_vars([
+        ("URLS".into(), "http://a;'http://b;c'".into()),
+        ("HOSTS".into(), "a:1 => 1;b:2=>2".into()),
+        ("LABELS".into(), "k=>v; k2 => 'v;2'".into()),
+        ("RANGE".into(), "Between=>1;'a;b'".into()),
+    ])
+    .with_options(crate::Options::delimited(
+        DelimitedParser::new()
+            .separator(";")
+            .key_separator("=>")
+            .quote('\''),
+    ));
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.urls, ["http://a", "http://b;c"]);
+    assert_eq!(config.labels["k"], "v");
+    assert_eq!(config.labels["k2"], "v;2");
+    assert_eq!(config.hosts["a:1"], 1);
+    assert_eq!(config.hosts["b:2"], 2);
+    assert_eq!(config.range, Range::Between(1, "a;b".into()));
+}
+
+#[test]
+fn test_delimited_quoted_str() {
+    use alloc::{string::String, vec::Vec};
+    use serde::Deserialize;
+
+    #[derive(serde_derive::Deserialize)]
+    struct Config {
+        name: String,
+        list: Vec<String>,
+    }
+
+    let vars = [("NAME", "'a;b'"), ("LIST", "'x;y';\"q\"")];
+    let options = crate::Options::delimited(DelimitedParser::new().separator(";").quote('\''));
+
+    let de = crate::Deserializer::from_str_vars(vars).with_options(options);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.name, "a;b");
+    assert_eq!(config.list, ["x;y", "\"q\""]);
+
+    let de = crate::BorrowedDeserializer::from_vars(&vars).with_options(options);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.name, "a;b");
+}
+
+#[test]
+fn test_nested() {
+    use alloc::{collections::BTreeMap, string::String, vec, vec::Vec};
+    use serde::Deserialize;
+
+    #[derive(serde_derive::Deserialize)]
+    struct Config {
+        matrix: Vec<Vec<u32>>,
+        wrapped: Vec<Vec<u32>>,
+        groups: BTreeMap<String, Vec<String>>,
+    }
+
+    let de = crate::Deserializer::from_vars([
+        ("MATRIX".into(), "[1, 2], [], [3]".into()),
+        ("WRAPPED".into(), "[[1, 2], [3]]".into()),
+        ("GROUPS".into(), "a: [x, \"y]\"], b: [z]".into()),
+    ]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.matrix, [vec![1, 2], vec![], vec![3]]);
+    assert_eq!(config.wrapped, [vec![1, 2], vec![3]]);
+    assert_eq!(config.groups["a"], ["x", "y]"]);
+    assert_eq!(config.groups["b"], ["z"]);
+}
+
+#[test]
+fn test_unbalanced_brackets() {
+    use alloc::{collections::BTreeMap, string::String, vec::Vec};
+    use serde::Deserialize;
+
+    #[derive(serde_derive::Deserialize)]
+    struct Config {
+        open: Vec<String>,
+        close: Vec<String>,
+        quoted: Vec<String>,
+        wrapped: Vec<String>,
+        labels: BTreeMap<String, String>,
+    }
+
+    let de = crate::Deserializer::from_str_vars([
+        ("OPEN", "a[b, c"),
+        ("CLOSE", "a]b, [c"),
+        ("QUOTED", "\"x[y\", z"),
+        ("WRAPPED", "[a]"),
+        ("LABELS", "k: \"v[\", k2: w"),
+    ]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.open, ["a[b", "c"]);
+    assert_eq!(config.close, ["a]b", "[c"]);
+    assert_eq!(config.quoted, ["x[y", "z"]);
+    assert_eq!(config.wrapped, ["a"]);
+    assert_eq!(config.labels["k"], "v[");
+    assert_eq!(config.labels["k2"], "w");
+}
+
+#[test]
+#[should_panic(expected = "separator must not be empty")]
+fn test_empty_separator() {
+    let _ = DelimitedParser::new().separator("");
 }
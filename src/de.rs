@@ -0,0 +1,2796 @@
+use alloc::{
+    borrow::{Cow, ToOwned},
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+use serde::de::{self, Visitor};
+
+use crate::{
+    dotenv,
+    recover::{locate_recorded, recorded, recover, Collected},
+    suggest::suggest,
+    BasicParser, DelimitedParser, Error, FromStrParser, KeyCase, MapKeys, Parser,
+};
+
+#[cfg(any(feature = "std", test))]
+use crate::ErrorKind;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Options<P> {
+    /// Controls how values are parsed from env var values.
+    parser: P,
+
+    /// Controls how names of fields are matched with env var names when
+    /// deserializing struct from map of env vars.
+    key_case: KeyCase,
+
+    /// Controls whether to continue after malformed values
+    /// and report all errors at once.
+    collect_errors: bool,
+
+    /// Controls whether to remove values from all error messages.
+    redact_values: bool,
+
+    /// Patterns of env var names whose values are removed from error messages.
+    redact_keys: &'static [&'static str],
+
+    /// Controls whether to fail on env vars that don't map to any field.
+    deny_unknown_vars: bool,
+
+    /// Separator between names of nested fields in env var names.
+    nesting_separator: &'static str,
+
+    /// Controls how conflicting env vars are handled.
+    conflicts: Conflicts,
+
+    /// Transform applied to map keys taken from env var names.
+    map_keys: MapKeys,
+
+    /// Controls whether empty or whitespace-only values deserialize as `None`.
+    empty_as_none: bool,
+
+    /// Values that deserialize as `None`, ignoring ASCII case.
+    none_literals: &'static [&'static str],
+
+    /// Controls how env vars with names that are not valid UTF-8 are handled.
+    skipped_vars: SkippedVars,
+}
+
+/// Policy for env vars that conflict with each other.
+///
+/// Env var conflicts when it matches several fields, like `DB_POOL_SIZE`
+/// for fields `db` and `db_pool`, when it is set both as a value and as a group
+/// of nested vars, like `DB` and `DB_HOST`, or when several vars map to the same
+/// field or index, like `TAGS_0` and `tags_0` with [`KeyCase::Insensitive`].
+#[derive(Clone, Copy, Debug, Default)]
+pub enum Conflicts {
+    /// Longest field name wins, values shadow nested vars
+    /// and later vars override earlier ones silently.
+    #[default]
+    Ignore,
+
+    /// Conflicts are resolved as with [`Conflicts::Ignore`]
+    /// and reported to the callback.
+    Warn(fn(&Error)),
+
+    /// Conflicts are reported as errors with [`ErrorKind::Conflict`] kind.
+    Deny,
+}
+
+/// Policy for env vars with names that are not valid UTF-8.
+///
+/// Such vars can't map to any field and are skipped by [`Deserializer::from_env_vars_os`].
+/// Their names are available with [`Deserializer::skipped_vars`] regardless of the policy.
+#[derive(Clone, Copy, Debug, Default)]
+pub enum SkippedVars {
+    /// Vars are skipped silently.
+    Ignore,
+
+    /// Vars are skipped and reported to the callback.
+    Warn(fn(&Error)),
+
+    /// Vars that look like they belong to deserialized struct are reported
+    /// as errors with [`ErrorKind::InvalidUtf8`] kind, e.g. `DB_\xff` for field `db`.
+    /// Other vars are skipped silently.
+    #[default]
+    Deny,
+}
+
+type DefaultOptions = Options<BasicParser>;
+
+impl<P> Options<P> {
+    /// Returns default options with specified parser.
+    pub const fn new(parser: P) -> Self {
+        Options {
+            parser,
+            key_case: KeyCase::Upper,
+            collect_errors: false,
+            redact_values: false,
+            redact_keys: &[],
+            deny_unknown_vars: false,
+            nesting_separator: "_",
+            conflicts: Conflicts::Ignore,
+            map_keys: MapKeys::Keep,
+            empty_as_none: false,
+            none_literals: &[],
+            skipped_vars: SkippedVars::Deny,
+        }
+    }
+
+    /// Replaces parser, keeping other options.
+    pub fn parser<X>(self, parser: X) -> Options<X> {
+        Options {
+            parser,
+            key_case: self.key_case,
+            collect_errors: self.collect_errors,
+            redact_values: self.redact_values,
+            redact_keys: self.redact_keys,
+            deny_unknown_vars: self.deny_unknown_vars,
+            nesting_separator: self.nesting_separator,
+            conflicts: self.conflicts,
+            map_keys: self.map_keys,
+            empty_as_none: self.empty_as_none,
+            none_literals: self.none_literals,
+            skipped_vars: self.skipped_vars,
+        }
+    }
+
+    /// Controls whether to compare uppercase names of fields
+    /// with env var names. Enabled by default.
+    ///
+    /// Shorthand for [`KeyCase::Upper`] or [`KeyCase::Exact`] key case.
+    pub const fn ident_upper(mut self, ident_upper: bool) -> Self {
+        self.key_case = if ident_upper {
+            KeyCase::Upper
+        } else {
+            KeyCase::Exact
+        };
+        self
+    }
+
+    /// Sets policy for matching names of fields with env var names.
+    pub const fn key_case(mut self, key_case: KeyCase) -> Self {
+        self.key_case = key_case;
+        self
+    }
+
+    /// Controls whether to continue deserialization after malformed values
+    /// and report all collected errors at once.
+    ///
+    /// When enabled, value that fails to parse is recorded as an error
+    /// and replaced with a placeholder, so the rest of the fields are still checked,
+    /// including fields of nested structs.
+    /// Resulting error has [`ErrorKind::Multiple`] kind if more than one error was found.
+    ///
+    /// Errors raised by the deserialized types themselves,
+    /// such as missing fields, unknown enum variants or values rejected by custom visitors,
+    /// can't be replaced with a placeholder and stop deserialization of the whole value.
+    /// To report as many errors as possible before that,
+    /// single values are visited before nested structs at every level.
+    pub const fn collect_errors(mut self, collect: bool) -> Self {
+        self.collect_errors = collect;
+        self
+    }
+
+    /// Controls whether to replace offending values in all error messages
+    /// with a `<redacted>` placeholder.
+    pub const fn redact_values(mut self, redact: bool) -> Self {
+        self.redact_values = redact;
+        self
+    }
+
+    /// Sets patterns of env var names whose values are replaced
+    /// with a `<redacted>` placeholder in error messages.
+    ///
+    /// Patterns are matched against full env var names, ignoring ASCII case.
+    /// `*` matches any sequence of characters, e.g. `*_PASSWORD`, `*_TOKEN` or `*_SECRET`.
+    pub const fn redact_keys(mut self, patterns: &'static [&'static str]) -> Self {
+        self.redact_keys = patterns;
+        self
+    }
+
+    /// Controls whether to fail on env vars that don't map to any struct field
+    /// at any nesting level, suggesting the closest valid name.
+    ///
+    /// Vars grouped into maps are always accepted.
+    /// Nested vars shadowed by a value, like `PORT_TYPO` with `PORT`, are unknown.
+    /// Meant to be used with [`Deserializer::from_prefixed_env_vars`],
+    /// as process env usually contains many unrelated vars.
+    pub const fn deny_unknown_vars(mut self, deny: bool) -> Self {
+        self.deny_unknown_vars = deny;
+        self
+    }
+
+    /// Sets separator between names of nested fields, map keys and indices
+    /// in env var names. Default is `_`.
+    ///
+    /// With `__` separator `APP__DB__MAX_CONNECTIONS` maps to `app.db.max_connections`,
+    /// regardless of underscores in field names.
+    /// `.` is also supported.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `separator` is empty.
+    pub const fn nesting_separator(mut self, separator: &'static str) -> Self {
+        assert!(!separator.is_empty(), "nesting separator must not be empty");
+        self.nesting_separator = separator;
+        self
+    }
+
+    /// Sets policy for env vars that conflict with each other.
+    pub const fn conflicts(mut self, conflicts: Conflicts) -> Self {
+        self.conflicts = conflicts;
+        self
+    }
+
+    /// Sets transform applied to map keys taken from env var names.
+    /// Keys are kept as is by default.
+    ///
+    /// With [`MapKeys::Kebab`] `LABELS_TEAM_NAME` gives `team-name` key in `labels` map.
+    pub const fn map_keys(mut self, map_keys: MapKeys) -> Self {
+        self.map_keys = map_keys;
+        self
+    }
+
+    /// Controls whether empty or whitespace-only values of `Option` fields
+    /// deserialize as `None` instead of `Some`. Disabled by default.
+    pub const fn empty_as_none(mut self, empty_as_none: bool) -> Self {
+        self.empty_as_none = empty_as_none;
+        self
+    }
+
+    /// Sets values of `Option` fields that deserialize as `None`,
+    /// e.g. `null` or `none`. Values are trimmed and compared ignoring ASCII case.
+    pub const fn none_literals(mut self, literals: &'static [&'static str]) -> Self {
+        self.none_literals = literals;
+        self
+    }
+
+    /// Sets policy for env vars with names that are not valid UTF-8.
+    /// By default such vars are denied if they look like they belong to deserialized struct.
+    pub const fn skipped_vars(mut self, policy: SkippedVars) -> Self {
+        self.skipped_vars = policy;
+        self
+    }
+
+    /// Checks if value of `Option` field deserializes as `None`.
+    fn is_none(&self, value: &str) -> bool {
+        let value = value.trim();
+        (self.empty_as_none && value.is_empty())
+            || self
+                .none_literals
+                .iter()
+                .any(|literal| literal.eq_ignore_ascii_case(value))
+    }
+}
+
+impl Options<BasicParser> {
+    pub const fn basic() -> Self {
+        Options::new(BasicParser)
+    }
+}
+
+impl Options<DelimitedParser> {
+    pub const fn delimited(parser: DelimitedParser) -> Self {
+        Options::new(parser)
+    }
+}
+
+impl Options<FromStrParser> {
+    pub const fn from_str() -> Self {
+        Options::new(FromStrParser)
+    }
+}
+
+impl Default for Options<BasicParser> {
+    fn default() -> Self {
+        Self::basic()
+    }
+}
+
+pub struct Deserializer<O = DefaultOptions> {
+    pub(crate) vars: Vec<(String, String)>,
+    options: O,
+
+    /// Prefix stripped from env var names, used in error messages.
+    prefix: String,
+
+    /// Env vars that are not valid UTF-8.
+    #[cfg(feature = "std")]
+    os_vars: OsVars,
+}
+
+/// Env vars taken from `OsString` source.
+#[cfg(feature = "std")]
+#[derive(Default)]
+struct OsVars {
+    /// Vars with values kept as is.
+    values: Vec<(String, std::ffi::OsString)>,
+
+    /// Full names of vars that are not valid UTF-8, with invalid sequences replaced.
+    skipped: Vec<String>,
+}
+
+impl Deserializer {
+    /// Creates deserializer from key-value pairs.
+    pub fn from_vars(vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        Deserializer {
+            vars: vars.into_iter().collect(),
+            options: DefaultOptions::basic(),
+            prefix: String::new(),
+            #[cfg(feature = "std")]
+            os_vars: OsVars::default(),
+        }
+    }
+
+    /// Creates deserializer from borrowed key-value pairs,
+    /// e.g. a static slice of pairs baked in at build time.
+    pub fn from_str_vars<'a>(vars: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        Deserializer::from_vars(
+            vars.into_iter()
+                .map(|(key, value)| (key.to_owned(), value.to_owned())),
+        )
+    }
+}
+
+impl Deserializer {
+    #[cfg(feature = "std")]
+    pub fn from_env_vars() -> Self {
+        let vars = std::env::vars_os().filter_map(|(key, value)| {
+            Some((key.to_str()?.to_owned(), value.to_str()?.to_owned()))
+        });
+
+        Deserializer::from_vars(vars)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_prefixed_env_vars(prefix: &str) -> Self {
+        let vars = std::env::vars_os().filter_map(|(key, value)| {
+            let key = key.to_str()?;
+            if let Some(key_suffix) = key.strip_prefix(prefix) {
+                Some((key_suffix.to_owned(), value.to_str()?.to_owned()))
+            } else {
+                None
+            }
+        });
+
+        Deserializer {
+            prefix: prefix.to_owned(),
+            ..Deserializer::from_vars(vars)
+        }
+    }
+
+    /// Creates deserializer from env vars of the current process,
+    /// keeping values that are not valid UTF-8.
+    ///
+    /// Values are deserialized into `OsString` fields as is,
+    /// `PathBuf` fields need [`os_path`] for that.
+    /// Byte fields get encoded bytes of values as is, without base64 decoding.
+    /// Other types fail with [`ErrorKind::InvalidUtf8`] if value is not valid UTF-8.
+    ///
+    /// Vars with names that are not valid UTF-8 are skipped
+    /// and reported according to [`Options::skipped_vars`].
+    #[cfg(feature = "std")]
+    pub fn from_env_vars_os() -> Self {
+        Deserializer::from_prefixed_env_vars_os("")
+    }
+
+    /// Same as [`Deserializer::from_env_vars_os`],
+    /// but takes only vars that start with `prefix` and strips it.
+    #[cfg(feature = "std")]
+    pub fn from_prefixed_env_vars_os(prefix: &str) -> Self {
+        Deserializer::from_os_vars(prefix, std::env::vars_os())
+    }
+
+    /// Creates deserializer from pairs that may be not valid UTF-8,
+    /// taking only vars that start with `prefix`.
+    #[cfg(feature = "std")]
+    fn from_os_vars(
+        prefix: &str,
+        vars: impl IntoIterator<Item = (std::ffi::OsString, std::ffi::OsString)>,
+    ) -> Self {
+        let mut de = Deserializer {
+            prefix: prefix.to_owned(),
+            ..Deserializer::from_vars([])
+        };
+
+        for (key, value) in vars {
+            if !key.as_encoded_bytes().starts_with(prefix.as_bytes()) {
+                continue;
+            }
+            match key.to_str() {
+                Some(key) => {
+                    let key = key[prefix.len()..].to_owned();
+                    de.os_vars.values.push((key, value));
+                }
+                None => {
+                    let key = key.to_string_lossy().into_owned();
+                    de.os_vars.skipped.push(key);
+                }
+            }
+        }
+
+        de
+    }
+
+    /// Creates deserializer from contents of a dotenv file.
+    ///
+    /// Supports comments, `export` prefixes, single-quoted literal values
+    /// and double-quoted values with escapes. Quoted values may span multiple lines.
+    pub fn from_dotenv_str(s: &str) -> Result<Self, Error> {
+        Ok(Deserializer::from_vars(dotenv::parse(s)?))
+    }
+
+    /// Creates deserializer from dotenv file at specified path.
+    #[cfg(feature = "std")]
+    pub fn from_dotenv_path(path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        let s =
+            std::fs::read_to_string(path).map_err(|err| Error::with_source(ErrorKind::Io, err))?;
+        Deserializer::from_dotenv_str(&s)
+    }
+}
+
+impl<O> Deserializer<O> {
+    /// Set options of the deserializer.
+    pub fn with_options<X>(self, options: X) -> Deserializer<X> {
+        Deserializer {
+            vars: self.vars,
+            options,
+            prefix: self.prefix,
+            #[cfg(feature = "std")]
+            os_vars: self.os_vars,
+        }
+    }
+
+    /// Returns options of the deserializer.
+    pub fn options(&self) -> &O {
+        &self.options
+    }
+
+    /// Returns mutable reference to options of the deserializer.
+    pub fn options_mut(&mut self) -> &mut O {
+        &mut self.options
+    }
+
+    /// Returns full names of env vars that were skipped
+    /// because they are not valid UTF-8, with invalid sequences replaced.
+    #[cfg(feature = "std")]
+    pub fn skipped_vars(&self) -> &[String] {
+        &self.os_vars.skipped
+    }
+}
+
+impl<P> Deserializer<Options<P>>
+where
+    P: Parser,
+{
+    /// Runs deserialization of the group of all vars,
+    /// collecting errors if configured.
+    fn run<'de, T>(
+        self,
+        fields: Option<&'static [&'static str]>,
+        f: impl FnOnce(Group<'_, 'de, P>) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let vars = self
+            .vars
+            .into_iter()
+            .map(|(key, value)| (key, Value::Str(Cow::Owned(value))));
+
+        #[cfg(feature = "std")]
+        let (vars, skipped) = {
+            let values = self.os_vars.values.into_iter().map(|(key, raw)| {
+                let lossy = raw.to_string_lossy().into_owned();
+                (key, Value::Os { raw, lossy })
+            });
+            (vars.chain(values), &*self.os_vars.skipped)
+        };
+        #[cfg(not(feature = "std"))]
+        let skipped = &[];
+
+        let skipped = Skipped {
+            vars: skipped,
+            fields,
+        };
+        run(vars.collect(), skipped, self.options, &self.prefix, f)
+    }
+}
+
+/// Vars skipped because their names are not valid UTF-8.
+struct Skipped<'a> {
+    /// Full names of skipped vars.
+    vars: &'a [String],
+
+    /// Fields of deserialized struct, if struct is deserialized.
+    fields: Option<&'static [&'static str]>,
+}
+
+/// Runs deserialization of the group of `vars`,
+/// collecting errors if configured.
+///
+/// `skipped` vars are handled according to [`Options::skipped_vars`].
+fn run<'de, P, T>(
+    vars: Vec<(String, Value<'de>)>,
+    skipped: Skipped<'_>,
+    options: Options<P>,
+    prefix: &str,
+    f: impl FnOnce(Group<'_, 'de, P>) -> Result<T, Error>,
+) -> Result<T, Error>
+where
+    P: Parser,
+{
+    let collected = Collected::default();
+    let warnings = Collected::default();
+
+    for var in skipped.vars {
+        match options.skipped_vars {
+            SkippedVars::Ignore => {}
+            SkippedVars::Warn(warn) => warn(&Error::invalid_utf8_name(var)),
+            SkippedVars::Deny => {
+                let key = &var[prefix.len()..];
+                let matches = match skipped.fields {
+                    Some(fields) => {
+                        let separator = options.nesting_separator;
+                        match_field(key, fields, options.key_case, separator).is_some()
+                    }
+                    None => true,
+                };
+                if matches {
+                    collected.borrow_mut().push(Error::invalid_utf8_name(key));
+                }
+            }
+        }
+    }
+
+    let result = f(Group {
+        vars,
+        options,
+        collected: options.collect_errors.then_some(&collected),
+        warnings: matches!(options.conflicts, Conflicts::Warn(_)).then_some(&warnings),
+    });
+
+    if let Conflicts::Warn(warn) = options.conflicts {
+        for warning in warnings.into_inner() {
+            warn(&warning.with_prefix(prefix));
+        }
+    }
+
+    let mut errors = collected.into_inner();
+    let result = match result {
+        Ok(value) if errors.is_empty() => Ok(value),
+        Ok(_) => Err(Error::collect(errors)),
+        Err(err) => {
+            errors.push(err);
+            Err(Error::collect(errors))
+        }
+    };
+    result.map_err(|err| {
+        err.with_prefix(prefix)
+            .redact_matching(options.redact_values, options.redact_keys)
+    })
+}
+
+macro_rules! deserialize_group {
+    () => {
+        type Error = Error;
+
+        fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: de::Visitor<'de>,
+        {
+            self.run(None, |group| group.deserialize_any(visitor))
+        }
+
+        fn deserialize_struct<V>(
+            self,
+            name: &'static str,
+            fields: &'static [&'static str],
+            visitor: V,
+        ) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.run(Some(fields), |group| {
+                group.deserialize_struct(name, fields, visitor)
+            })
+        }
+
+        fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.run(None, |group| group.deserialize_map(visitor))
+        }
+
+        fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            visitor.visit_some(self)
+        }
+
+        serde::forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+            unit unit_struct newtype_struct seq tuple tuple_struct enum identifier ignored_any
+        }
+    };
+}
+
+impl<'de, P> de::Deserializer<'de> for Deserializer<Options<P>>
+where
+    P: Parser,
+{
+    deserialize_group!();
+}
+
+/// Deserializer over borrowed env vars.
+///
+/// Unlike [`Deserializer`], string values that need no unescaping
+/// are borrowed, so they can be deserialized into `&'de str` or `Cow<'de, str>` fields.
+pub struct BorrowedDeserializer<'de, O = DefaultOptions> {
+    vars: &'de [(&'de str, &'de str)],
+    options: O,
+}
+
+impl<'de> BorrowedDeserializer<'de> {
+    pub fn from_vars(vars: &'de [(&'de str, &'de str)]) -> Self {
+        BorrowedDeserializer {
+            vars,
+            options: DefaultOptions::basic(),
+        }
+    }
+}
+
+impl<'de, O> BorrowedDeserializer<'de, O> {
+    /// Set options of the deserializer.
+    pub fn with_options<X>(self, options: X) -> BorrowedDeserializer<'de, X> {
+        BorrowedDeserializer {
+            vars: self.vars,
+            options,
+        }
+    }
+
+    /// Returns options of the deserializer.
+    pub fn options(&self) -> &O {
+        &self.options
+    }
+
+    /// Returns mutable reference to options of the deserializer.
+    pub fn options_mut(&mut self) -> &mut O {
+        &mut self.options
+    }
+}
+
+impl<'de, P> BorrowedDeserializer<'de, Options<P>>
+where
+    P: Parser,
+{
+    /// Runs deserialization of the group of all vars,
+    /// collecting errors if configured.
+    fn run<T>(
+        self,
+        fields: Option<&'static [&'static str]>,
+        f: impl FnOnce(Group<'_, 'de, P>) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let vars = self
+            .vars
+            .iter()
+            .map(|&(key, value)| (key.to_owned(), Value::Str(Cow::Borrowed(value))))
+            .collect();
+        let skipped = Skipped { vars: &[], fields };
+        run(vars, skipped, self.options, "", f)
+    }
+}
+
+impl<'de, P> de::Deserializer<'de> for BorrowedDeserializer<'de, Options<P>>
+where
+    P: Parser,
+{
+    deserialize_group!();
+}
+
+/// Deserializer for a group of vars.
+/// Either all vars of [`Deserializer`] or vars with common prefix.
+struct Group<'a, 'de, P> {
+    vars: Vec<(String, Value<'de>)>,
+    options: Options<P>,
+    collected: Option<&'a Collected>,
+    warnings: Option<&'a Collected>,
+}
+
+impl<P> Group<'_, '_, P> {
+    /// Records error if errors are collected, otherwise returns it.
+    fn fail(&self, err: Error) -> Result<(), Error> {
+        match self.collected {
+            Some(collected) => {
+                collected.borrow_mut().push(err);
+                Ok(())
+            }
+            None => Err(err),
+        }
+    }
+
+    /// Handles conflict according to the policy.
+    fn conflict(&self, err: Error) -> Result<(), Error> {
+        match self.options.conflicts {
+            Conflicts::Ignore => Ok(()),
+            Conflicts::Warn(_) => {
+                if let Some(warnings) = self.warnings {
+                    warnings.borrow_mut().push(err);
+                }
+                Ok(())
+            }
+            Conflicts::Deny => self.fail(err),
+        }
+    }
+
+    /// Handles conflict caused by `key` var.
+    /// Shadowed nested vars don't map to any field and are unknown in strict mode.
+    fn shadowed(&self, key: &str, conflict: Conflict) -> Result<(), Error> {
+        if self.options.deny_unknown_vars {
+            for var in &conflict.shadowed {
+                self.fail(Error::unknown_var(var, None))?;
+            }
+        }
+        self.conflict(Error::conflict(key, conflict.message.to_owned()))
+    }
+}
+
+impl<'de, P> de::Deserializer<'de> for Group<'_, 'de, P>
+where
+    P: Parser,
+{
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_map(Map {
+            next_value: None,
+            vars: self
+                .vars
+                .into_iter()
+                .map(|(key, value)| Entry {
+                    var: key.clone(),
+                    key,
+                    access: VarAccess::Value(value),
+                })
+                .collect(),
+            options: self.options,
+            collected: self.collected,
+            warnings: self.warnings,
+        })
+    }
+
+    fn deserialize_struct<V>(
+        mut self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let key_case = self.options.key_case;
+        let separator = self.options.nesting_separator;
+        let mut vars = Vec::<Entry<'de>>::new();
+
+        for (key, value) in core::mem::take(&mut self.vars) {
+            if !matches!(self.options.conflicts, Conflicts::Ignore) {
+                let mut names = matching_fields(&key, fields, key_case, separator)
+                    .map(|(name, _)| name)
+                    .peekable();
+                if let (Some(first), Some(_)) = (names.next(), names.peek()) {
+                    let names = names.fold(format!("`{}`", first), |names, name| {
+                        format!("{}, `{}`", names, name)
+                    });
+                    let message = format!("env var matches several fields: {}", names);
+                    self.conflict(Error::conflict(&key, message))?;
+                }
+            }
+
+            let conflict = match match_field(&key, fields, key_case, separator) {
+                Some((name, None)) => add_var(&mut vars, name, &key, None, value, separator),
+                Some((name, Some(suffix))) => {
+                    let var = &key[..key.len() - suffix.len() - separator.len()];
+                    add_var(&mut vars, name, var, Some(suffix), value, separator)
+                }
+                None if self.options.deny_unknown_vars => {
+                    let suggestion = suggest(&key, fields, key_case, separator);
+                    self.fail(Error::unknown_var(&key, suggestion))?;
+                    None
+                }
+                None => add_var(&mut vars, &key, &key, None, value, separator),
+            };
+
+            if let Some(conflict) = conflict {
+                self.shadowed(&key, conflict)?;
+            }
+        }
+
+        if self.collected.is_some() {
+            // Visit single values before nested groups,
+            // so that missing fields in nested groups don't hide malformed values.
+            vars.sort_by_key(|entry| matches!(entry.access, VarAccess::Value(_)));
+        }
+
+        visitor
+            .visit_map(Map {
+                next_value: None,
+                vars,
+                options: self.options,
+                collected: self.collected,
+                warnings: self.warnings,
+            })
+            .map_err(|err| match err.unlocated_missing_field() {
+                Some(field) => err.with_key(&key_case.var_name(field), field, separator),
+                None => err,
+            })
+    }
+
+    fn deserialize_seq<V>(mut self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        // Vars are grouped by leading index, e.g. `0_HOST` and `0_PORT`.
+        // Elements are ordered by index, gaps between indices are skipped.
+        let mut elements = Vec::<Entry<'de>>::new();
+
+        let key_case = self.options.key_case;
+        for (key, value) in core::mem::take(&mut self.vars) {
+            let (head, suffix) =
+                match key_case.split_separator(&key, self.options.nesting_separator) {
+                    Some((head, suffix)) => (head, Some(suffix)),
+                    None => (&*key, None),
+                };
+
+            let Some(index) = parse_index(head) else {
+                if self.options.deny_unknown_vars {
+                    self.fail(Error::unknown_var(&key, None))?;
+                }
+                continue;
+            };
+
+            let separator = self.options.nesting_separator;
+            if let Some(conflict) = add_var(
+                &mut elements,
+                &index.to_string(),
+                head,
+                suffix,
+                value,
+                separator,
+            ) {
+                self.shadowed(&key, conflict)?;
+            }
+        }
+
+        // Elements are popped from the end.
+        elements.sort_by_key(|entry| core::cmp::Reverse(entry.key.parse::<usize>().ok()));
+
+        visitor.visit_seq(Seq {
+            elements,
+            options: self.options,
+            collected: self.collected,
+            warnings: self.warnings,
+        })
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(KeyedMap {
+            vars: self.vars,
+            next_value: None,
+            options: self.options,
+            collected: self.collected,
+            warnings: self.warnings,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct newtype_struct enum identifier
+    }
+}
+
+/// Parses index of sequence element.
+/// Only plain decimal numbers without leading zeros are accepted.
+fn parse_index(s: &str) -> Option<usize> {
+    let canonical = s.bytes().all(|b| b.is_ascii_digit()) && (s == "0" || !s.starts_with('0'));
+    if canonical {
+        s.parse().ok()
+    } else {
+        None
+    }
+}
+
+/// Finds the field that env var maps to.
+///
+/// Returns the field and, if var belongs to a nested group,
+/// the rest of the var name after the separator.
+/// Longest field name wins if several fields match.
+fn match_field<'k>(
+    key: &'k str,
+    fields: &[&'static str],
+    key_case: KeyCase,
+    separator: &str,
+) -> Option<(&'static str, Option<&'k str>)> {
+    matching_fields(key, fields, key_case, separator).max_by_key(|(name, _)| name.len())
+}
+
+/// Returns all fields that env var may map to.
+fn matching_fields<'k, 'f>(
+    key: &'k str,
+    fields: &'f [&'static str],
+    key_case: KeyCase,
+    separator: &'f str,
+) -> impl Iterator<Item = (&'static str, Option<&'k str>)> + 'f
+where
+    'k: 'f,
+{
+    fields.iter().filter_map(move |name| {
+        let suffix = key_case.strip_prefix(key, name)?;
+        if suffix.is_empty() {
+            Some((*name, None))
+        } else {
+            Some((*name, Some(key_case.strip_separator(suffix, separator)?)))
+        }
+    })
+}
+
+/// Conflict between vars of the same entry.
+struct Conflict {
+    /// Description of the conflict.
+    message: &'static str,
+
+    /// Names of nested vars shadowed by value of the entry.
+    shadowed: Vec<String>,
+}
+
+/// Adds var to the entry with `key`, creating the entry if needed.
+/// Var with `suffix` goes into the nested group of the entry.
+///
+/// Value of the entry shadows its nested group.
+/// Returns the conflict if any vars are shadowed.
+fn add_var<'de>(
+    entries: &mut Vec<Entry<'de>>,
+    key: &str,
+    var: &str,
+    suffix: Option<&str>,
+    value: Value<'de>,
+    separator: &str,
+) -> Option<Conflict> {
+    let index = entries.iter().position(|entry| entry.key == key);
+
+    let conflict = |message, shadowed| Some(Conflict { message, shadowed });
+    match (index, suffix) {
+        (Some(index), None) => {
+            let entry = &mut entries[index];
+            let access = core::mem::replace(&mut entry.access, VarAccess::Value(value));
+            match access {
+                VarAccess::Vars(map) => {
+                    let shadowed = map
+                        .into_iter()
+                        .map(|(suffix, _)| format!("{}{}{}", entry.var, separator, suffix))
+                        .collect();
+                    conflict("env var shadows nested vars with the same name", shadowed)
+                }
+                VarAccess::Value(_) => conflict(
+                    "env var overrides another var with the same name",
+                    Vec::new(),
+                ),
+            }
+        }
+        (None, None) => {
+            entries.push(Entry {
+                key: key.to_owned(),
+                var: var.to_owned(),
+                access: VarAccess::Value(value),
+            });
+            None
+        }
+        (Some(index), Some(suffix)) => match &mut entries[index].access {
+            VarAccess::Vars(map) => match map.iter().position(|(ident, _)| ident == suffix) {
+                Some(index) => {
+                    map[index].1 = value;
+                    conflict(
+                        "env var overrides another var with the same name",
+                        Vec::new(),
+                    )
+                }
+                None => {
+                    map.push((suffix.to_owned(), value));
+                    None
+                }
+            },
+            VarAccess::Value(_) => {
+                let shadowed = vec![format!("{}{}{}", var, separator, suffix)];
+                conflict("env var is shadowed by value with the same name", shadowed)
+            }
+        },
+        (None, Some(suffix)) => {
+            entries.push(Entry {
+                key: key.to_owned(),
+                var: var.to_owned(),
+                access: VarAccess::Vars(vec![(suffix.to_owned(), value)]),
+            });
+            None
+        }
+    }
+}
+
+/// Value of env var.
+#[derive(Clone)]
+enum Value<'de> {
+    Str(Cow<'de, str>),
+
+    /// Value taken from `OsString` source.
+    #[cfg(feature = "std")]
+    Os {
+        raw: std::ffi::OsString,
+
+        /// Value with invalid sequences replaced, used in error messages.
+        lossy: String,
+    },
+}
+
+impl<'de> Value<'de> {
+    fn as_str(&self) -> &str {
+        match self {
+            Value::Str(value) => value,
+            #[cfg(feature = "std")]
+            Value::Os { lossy, .. } => lossy,
+        }
+    }
+
+    /// Returns value if it is borrowed from the input.
+    fn borrowed(&self) -> Option<&'de str> {
+        match *self {
+            Value::Str(Cow::Borrowed(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    /// Returns value taken from `OsString` source.
+    #[cfg(feature = "std")]
+    fn raw(&self) -> Option<&std::ffi::OsStr> {
+        match self {
+            Value::Os { raw, .. } => Some(raw),
+            Value::Str(_) => None,
+        }
+    }
+}
+
+enum VarAccess<'de> {
+    Value(Value<'de>),
+    Vars(Vec<(String, Value<'de>)>),
+}
+
+/// Value or group of values with a key.
+struct Entry<'de> {
+    /// Key passed to the visitor.
+    key: String,
+
+    /// Name of the env var or common part of names of grouped env vars.
+    var: String,
+
+    access: VarAccess<'de>,
+}
+
+impl<'de> Entry<'de> {
+    /// Deserializes value or group of the entry,
+    /// locating errors at the entry.
+    fn deserialize<S, P>(
+        self,
+        seed: S,
+        options: Options<P>,
+        collected: Option<&Collected>,
+        warnings: Option<&Collected>,
+    ) -> Result<S::Value, Error>
+    where
+        S: de::DeserializeSeed<'de>,
+        P: Parser,
+    {
+        let errors_recorded = recorded(collected);
+        let warnings_recorded = recorded(warnings);
+
+        let result = match self.access {
+            VarAccess::Value(value) => {
+                seed.deserialize(ValueDeserializer::from_value(&value, options, collected))
+            }
+            VarAccess::Vars(vars) => seed.deserialize(Group {
+                vars,
+                options,
+                collected,
+                warnings,
+            }),
+        };
+
+        let separator = options.nesting_separator;
+        locate_recorded(collected, errors_recorded, &self.var, &self.key, separator);
+        locate_recorded(warnings, warnings_recorded, &self.var, &self.key, separator);
+
+        result.map_err(|err| err.with_key(&self.var, &self.key, options.nesting_separator))
+    }
+}
+
+struct Map<'a, 'de, P> {
+    next_value: Option<Entry<'de>>,
+    vars: Vec<Entry<'de>>,
+    options: Options<P>,
+    collected: Option<&'a Collected>,
+    warnings: Option<&'a Collected>,
+}
+
+impl<'de, P> de::MapAccess<'de> for Map<'_, 'de, P>
+where
+    P: Parser,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.vars.pop() {
+            Some(entry) => {
+                let key = seed
+                    .deserialize(de::value::StrDeserializer::new(&entry.key))
+                    .map(Some)?;
+                self.next_value = Some(entry);
+                Ok(key)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let Some(entry) = self.next_value.take() else {
+            panic!("next_value called before next_key");
+        };
+
+        entry.deserialize(seed, self.options, self.collected, self.warnings)
+    }
+}
+
+/// Sequence of values or groups of values with common index.
+struct Seq<'a, 'de, P> {
+    elements: Vec<Entry<'de>>,
+    options: Options<P>,
+    collected: Option<&'a Collected>,
+    warnings: Option<&'a Collected>,
+}
+
+impl<'de, P> de::SeqAccess<'de> for Seq<'_, 'de, P>
+where
+    P: Parser,
+{
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        match self.elements.pop() {
+            Some(entry) => entry
+                .deserialize(seed, self.options, self.collected, self.warnings)
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.elements.len())
+    }
+}
+
+/// Map of vars where values that need a struct
+/// are grouped by the first segment of var names.
+///
+/// For example `PRIMARY_URL` and `PRIMARY_POOL` become a struct under key `PRIMARY`.
+/// Other values are kept under full var names.
+struct KeyedMap<'a, 'de, P> {
+    vars: Vec<(String, Value<'de>)>,
+    next_value: Option<Entry<'de>>,
+    options: Options<P>,
+    collected: Option<&'a Collected>,
+    warnings: Option<&'a Collected>,
+}
+
+impl<'de, P> de::MapAccess<'de> for KeyedMap<'_, 'de, P>
+where
+    P: Parser,
+{
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.vars.pop() {
+            Some((key, value)) => {
+                let map_key = self
+                    .options
+                    .map_keys
+                    .apply(&key, self.options.nesting_separator);
+                let key_value = seed.deserialize(de::value::StrDeserializer::new(&map_key))?;
+                self.next_value = Some(Entry {
+                    var: key.clone(),
+                    key,
+                    access: VarAccess::Value(value),
+                });
+                Ok(Some(key_value))
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let Some(entry) = self.next_value.take() else {
+            panic!("next_value called before next_key");
+        };
+
+        entry.deserialize(seed, self.options, self.collected, self.warnings)
+    }
+
+    fn next_entry_seed<K, V>(
+        &mut self,
+        key_seed: K,
+        value_seed: V,
+    ) -> Result<Option<(K::Value, V::Value)>, Error>
+    where
+        K: de::DeserializeSeed<'de>,
+        V: de::DeserializeSeed<'de>,
+    {
+        let Some((key, value)) = self.vars.pop() else {
+            return Ok(None);
+        };
+
+        // Value is deserialized first, as its type decides what the key is.
+        let errors_recorded = recorded(self.collected);
+        let warnings_recorded = recorded(self.warnings);
+        let mut grouped = None;
+
+        let result = value_seed.deserialize(KeyedValue {
+            key: &key,
+            value: &value,
+            rest: &mut self.vars,
+            grouped: &mut grouped,
+            options: self.options,
+            collected: self.collected,
+            warnings: self.warnings,
+        });
+
+        let key = grouped.unwrap_or(key);
+
+        let separator = self.options.nesting_separator;
+        locate_recorded(self.collected, errors_recorded, &key, &key, separator);
+        locate_recorded(self.warnings, warnings_recorded, &key, &key, separator);
+
+        let value = result.map_err(|err| err.with_key(&key, &key, separator))?;
+        let key = self.options.map_keys.apply(&key, separator);
+        let key = key_seed.deserialize(de::value::StrDeserializer::new(&key))?;
+        Ok(Some((key, value)))
+    }
+}
+
+/// Deserializer for a value of [`KeyedMap`].
+///
+/// If struct is requested, takes vars with the same first segment from the rest of the map.
+/// Otherwise deserializes single value.
+struct KeyedValue<'m, 'a, 'de, P> {
+    key: &'m str,
+    value: &'m Value<'de>,
+    rest: &'m mut Vec<(String, Value<'de>)>,
+
+    /// Common first segment of grouped vars.
+    grouped: &'m mut Option<String>,
+
+    options: Options<P>,
+    collected: Option<&'a Collected>,
+    warnings: Option<&'a Collected>,
+}
+
+impl<'m, 'a: 'm, 'de, P> KeyedValue<'m, 'a, 'de, P>
+where
+    P: Copy,
+{
+    fn single(&self) -> ValueDeserializer<'m, 'de, P> {
+        ValueDeserializer::from_value(self.value, self.options, self.collected)
+    }
+}
+
+macro_rules! forward_to_single {
+    ($($deserialize:ident)*) => {$(
+        fn $deserialize<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.single().$deserialize(visitor)
+        }
+    )*};
+}
+
+impl<'de, P> de::Deserializer<'de> for KeyedValue<'_, '_, 'de, P>
+where
+    P: Parser,
+{
+    type Error = Error;
+
+    forward_to_single! {
+        deserialize_any deserialize_bool
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_unit deserialize_seq deserialize_map
+        deserialize_identifier
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let key_case = self.options.key_case;
+        let separator = self.options.nesting_separator;
+        let Some((head, suffix)) = key_case.split_separator(self.key, separator) else {
+            return self.single().deserialize_struct(name, fields, visitor);
+        };
+
+        let mut vars = Vec::new();
+        let mut index = 0;
+        while index < self.rest.len() {
+            let key = &self.rest[index].0;
+            let start = key
+                .strip_prefix(head)
+                .and_then(|suffix| key_case.strip_separator(suffix, separator))
+                .map(|suffix| key.len() - suffix.len());
+            match start {
+                Some(start) => {
+                    let (key, value) = self.rest.remove(index);
+                    vars.push((key[start..].to_owned(), value));
+                }
+                None => index += 1,
+            }
+        }
+        vars.push((suffix.to_owned(), self.value.clone()));
+
+        *self.grouped = Some(head.to_owned());
+
+        Group {
+            vars,
+            options: self.options,
+            collected: self.collected,
+            warnings: self.warnings,
+        }
+        .deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.options.is_none(self.value.as_str()) {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.single().deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.single().deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.single().deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.single().deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+pub(crate) struct ValueDeserializer<'a, 'de, P> {
+    value: &'a str,
+
+    /// Value borrowed from the input, if any.
+    borrowed: Option<&'de str>,
+
+    /// Value taken from `OsString` source, if so.
+    #[cfg(feature = "std")]
+    raw: Option<&'a std::ffi::OsStr>,
+
+    parser: P,
+
+    /// Whether value deserializes as `None`.
+    none: bool,
+
+    /// Storage for errors if they are collected.
+    collected: Option<&'a Collected>,
+}
+
+impl<'a, 'de, P> ValueDeserializer<'a, 'de, P>
+where
+    P: Copy,
+{
+    pub(crate) fn new(value: &'a str, parser: P) -> Self {
+        ValueDeserializer {
+            value,
+            borrowed: None,
+            #[cfg(feature = "std")]
+            raw: None,
+            parser,
+            none: false,
+            collected: None,
+        }
+    }
+
+    fn from_value(
+        value: &'a Value<'de>,
+        options: Options<P>,
+        collected: Option<&'a Collected>,
+    ) -> Self {
+        ValueDeserializer {
+            value: value.as_str(),
+            borrowed: value.borrowed(),
+            #[cfg(feature = "std")]
+            raw: value.raw(),
+            parser: options.parser,
+            none: options.is_none(value.as_str()),
+            collected,
+        }
+    }
+
+    /// Returns value as string, failing if it is not valid UTF-8.
+    fn text(&self) -> Result<&'a str, Error> {
+        #[cfg(feature = "std")]
+        if self.raw.is_some_and(|raw| raw.to_str().is_none()) {
+            return Err(Error::invalid_utf8_value());
+        }
+        Ok(self.value)
+    }
+}
+
+macro_rules! parse_num {
+    ($($deserialize:ident $parse:ident $visit:ident $zero:literal)*) => {$(
+        fn $deserialize<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            recover(
+                self.collected,
+                visitor,
+                |visitor| self.parser.$parse(self.text()?, visitor),
+                |visitor| visitor.$visit($zero),
+            )
+        }
+    )*};
+}
+
+fn empty_seq() -> de::value::SeqDeserializer<core::iter::Empty<u8>, Error> {
+    de::value::SeqDeserializer::new(core::iter::empty())
+}
+
+fn empty_map() -> de::value::MapDeserializer<'static, core::iter::Empty<(u8, u8)>, Error> {
+    de::value::MapDeserializer::new(core::iter::empty())
+}
+
+impl<'de, P> de::Deserializer<'de> for ValueDeserializer<'_, 'de, P>
+where
+    P: Parser,
+{
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: de::Visitor<'de>,
+    {
+        recover(
+            self.collected,
+            visitor,
+            |visitor| self.parser.parse_any(self.text()?, visitor),
+            |visitor| visitor.visit_str(""),
+        )
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.none {
+            visitor.visit_none()
+        } else {
+            visitor.visit_some(self)
+        }
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        recover(
+            self.collected,
+            visitor,
+            |visitor| self.parser.parse_bool(self.text()?, visitor),
+            |visitor| visitor.visit_bool(false),
+        )
+    }
+
+    parse_num! {
+        deserialize_i8 parse_i8 visit_i8 0
+        deserialize_i16 parse_i16 visit_i16 0
+        deserialize_i32 parse_i32 visit_i32 0
+        deserialize_i64 parse_i64 visit_i64 0
+        deserialize_i128 parse_i128 visit_i128 0
+        deserialize_u8 parse_u8 visit_u8 0
+        deserialize_u16 parse_u16 visit_u16 0
+        deserialize_u32 parse_u32 visit_u32 0
+        deserialize_u64 parse_u64 visit_u64 0
+        deserialize_u128 parse_u128 visit_u128 0
+        deserialize_f32 parse_f32 visit_f32 0.0
+        deserialize_f64 parse_f64 visit_f64 0.0
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        recover(
+            self.collected,
+            visitor,
+            |visitor| self.parser.parse_char(self.text()?, visitor),
+            |visitor| visitor.visit_char('\0'),
+        )
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        recover(
+            self.collected,
+            visitor,
+            |visitor| match self.borrowed {
+                Some(value) => self.parser.parse_borrowed_str(value, visitor),
+                None => self.parser.parse_str(self.text()?, visitor),
+            },
+            |visitor| visitor.visit_str(""),
+        )
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        #[cfg(feature = "std")]
+        if let Some(raw) = self.raw {
+            return visitor.visit_bytes(raw.as_encoded_bytes());
+        }
+
+        recover(
+            self.collected,
+            visitor,
+            |visitor| self.parser.parse_bytes(self.text()?, visitor),
+            |visitor| visitor.visit_bytes(&[]),
+        )
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_seq<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        recover(
+            self.collected,
+            visitor,
+            |visitor| self.parser.parse_seq(self.text()?, visitor),
+            |visitor| visitor.visit_seq(empty_seq()),
+        )
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_map<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        recover(
+            self.collected,
+            visitor,
+            |visitor| self.parser.parse_map(self.text()?, visitor),
+            |visitor| visitor.visit_map(empty_map()),
+        )
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        recover(
+            self.collected,
+            visitor,
+            |visitor| {
+                self.parser
+                    .parse_struct(self.text()?, name, fields, visitor)
+            },
+            |visitor| visitor.visit_map(empty_map()),
+        )
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        #[cfg(all(feature = "std", any(unix, windows)))]
+        if name == "OsString" {
+            let os = self.raw.unwrap_or(std::ffi::OsStr::new(self.value));
+            return visitor.visit_enum(OsStrAccess(os));
+        }
+
+        recover(
+            self.collected,
+            visitor,
+            |visitor| {
+                self.parser
+                    .parse_enum(self.text()?, name, variants, visitor)
+            },
+            |visitor| {
+                let variant = variants.first().copied().unwrap_or_default();
+                visitor.visit_enum(de::value::StrDeserializer::new(variant))
+            },
+        )
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        // Ignored values are skipped without parsing.
+        visitor.visit_unit()
+    }
+}
+
+/// Access to `OsString` value in the form serde expects,
+/// an enum with platform-specific variant.
+#[cfg(all(feature = "std", any(unix, windows)))]
+struct OsStrAccess<'a>(&'a std::ffi::OsStr);
+
+#[cfg(all(feature = "std", any(unix, windows)))]
+impl<'de> de::EnumAccess<'de> for OsStrAccess<'_> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        #[cfg(unix)]
+        let variant = "Unix";
+        #[cfg(windows)]
+        let variant = "Windows";
+
+        let variant = seed.deserialize(de::value::StrDeserializer::new(variant))?;
+        Ok((variant, self))
+    }
+}
+
+#[cfg(all(feature = "std", any(unix, windows)))]
+impl<'de> de::VariantAccess<'de> for OsStrAccess<'_> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Err(de::Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"unit variant",
+        ))
+    }
+
+    fn newtype_variant_seed<T>(self, seed: T) -> Result<T::Value, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        #[cfg(unix)]
+        let units = std::os::unix::ffi::OsStrExt::as_bytes(self.0)
+            .iter()
+            .copied();
+        #[cfg(windows)]
+        let units = std::os::windows::ffi::OsStrExt::encode_wide(self.0);
+
+        seed.deserialize(de::value::SeqDeserializer::new(units))
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"tuple variant",
+        ))
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(de::Error::invalid_type(
+            de::Unexpected::NewtypeVariant,
+            &"struct variant",
+        ))
+    }
+}
+
+/// Deserializes `PathBuf` the same way as `OsString`,
+/// so paths that are not valid UTF-8 are kept as is.
+///
+/// Use it with `#[serde(deserialize_with = "denvars::os_path")]`.
+#[cfg(all(feature = "std", any(unix, windows)))]
+pub fn os_path<'de, D>(deserializer: D) -> Result<std::path::PathBuf, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    <std::ffi::OsString as serde::Deserialize>::deserialize(deserializer).map(Into::into)
+}
+
+#[test]
+fn test_error_path() {
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Pool {
+        size: u32,
+        timeout: u32,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Database {
+        pool: Pool,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        database: Database,
+    }
+
+    let de = Deserializer::from_vars([
+        ("DATABASE_POOL_SIZE".to_owned(), "5".to_owned()),
+        ("DATABASE_POOL_TIMEOUT".to_owned(), "10".to_owned()),
+    ]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.database.pool.size, 5);
+    assert_eq!(config.database.pool.timeout, 10);
+
+    let de = Deserializer::from_vars([
+        ("DATABASE_POOL_SIZE".to_owned(), "abc".to_owned()),
+        ("DATABASE_POOL_TIMEOUT".to_owned(), "10".to_owned()),
+    ]);
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.var(), Some("DATABASE_POOL_SIZE"));
+    assert_eq!(err.path(), Some("database.pool.size"));
+
+    let de = Deserializer::from_vars([("DATABASE_POOL_SIZE".to_owned(), "1".to_owned())]);
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.var(), Some("DATABASE_POOL_TIMEOUT"));
+    assert_eq!(err.path(), Some("database.pool.timeout"));
+}
+
+#[test]
+fn test_collect_errors() {
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Database {
+        url: String,
+        pool_size: u32,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        port: u16,
+        debug: bool,
+        name: String,
+        database: Database,
+    }
+
+    let de = Deserializer::from_str_vars([
+        ("PORT", "8080"),
+        ("DEBUG", "yes"),
+        ("NAME", "app"),
+        ("DATABASE_URL", "postgres://"),
+        ("DATABASE_POOL_SIZE", "4"),
+    ])
+    .with_options(Options::basic().collect_errors(true));
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.port, 8080);
+    assert!(config.debug);
+    assert_eq!(config.name, "app");
+    assert_eq!(config.database.url, "postgres://");
+    assert_eq!(config.database.pool_size, 4);
+
+    let de = Deserializer::from_vars([
+        ("PORT".to_owned(), "http".to_owned()),
+        ("DEBUG".to_owned(), "maybe".to_owned()),
+        ("NAME".to_owned(), "app".to_owned()),
+        ("DATABASE_URL".to_owned(), "postgres://".to_owned()),
+        ("DATABASE_POOL_SIZE".to_owned(), "-1".to_owned()),
+    ])
+    .with_options(Options::basic().collect_errors(true));
+
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Multiple);
+
+    let mut vars = err
+        .errors()
+        .iter()
+        .map(|err| err.var().unwrap())
+        .collect::<Vec<_>>();
+    vars.sort();
+    assert_eq!(vars, ["DATABASE_POOL_SIZE", "DEBUG", "PORT"]);
+
+    #[derive(Debug, PartialEq, serde_derive::Deserialize)]
+    enum Mode {
+        Fast,
+        Slow,
+        #[serde(other)]
+        Other,
+    }
+
+    /// Accepts only `i64` values.
+    #[derive(Debug)]
+    struct Offset(i64);
+
+    impl<'de> Deserialize<'de> for Offset {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            struct OffsetVisitor;
+
+            impl<'de> Visitor<'de> for OffsetVisitor {
+                type Value = Offset;
+
+                fn expecting(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str("an offset")
+                }
+
+                fn visit_i64<E>(self, v: i64) -> Result<Offset, E> {
+                    Ok(Offset(v))
+                }
+            }
+
+            deserializer.deserialize_i64(OffsetVisitor)
+        }
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Service {
+        a_port: u16,
+        mode: Mode,
+        offset: Offset,
+        z_port: u16,
+    }
+
+    let de = Deserializer::from_str_vars([
+        ("A_PORT", "1"),
+        ("MODE", "Slow"),
+        ("OFFSET", "-2"),
+        ("Z_PORT", "3"),
+    ])
+    .with_options(Options::basic().collect_errors(true));
+    let service = Service::deserialize(de).unwrap();
+    assert_eq!(service.a_port, 1);
+    assert_eq!(service.mode, Mode::Slow);
+    assert_eq!(service.offset.0, -2);
+    assert_eq!(service.z_port, 3);
+
+    // Names accepted by the enum itself are not errors.
+    let de = Deserializer::from_str_vars([
+        ("A_PORT", "1"),
+        ("MODE", "bogus"),
+        ("OFFSET", "-2"),
+        ("Z_PORT", "3"),
+    ])
+    .with_options(Options::basic().collect_errors(true));
+    let service = Service::deserialize(de).unwrap();
+    assert_eq!(service.mode, Mode::Other);
+
+    let de = Deserializer::from_str_vars([
+        ("A_PORT", "x"),
+        ("MODE", "bogus"),
+        ("OFFSET", "y"),
+        ("Z_PORT", "z"),
+    ])
+    .with_options(Options::basic().collect_errors(true));
+    let err = Service::deserialize(de).unwrap_err();
+    let mut vars = err
+        .errors()
+        .iter()
+        .map(|err| err.var().unwrap())
+        .collect::<Vec<_>>();
+    vars.sort();
+    assert_eq!(vars, ["A_PORT", "OFFSET", "Z_PORT"]);
+}
+
+#[test]
+fn test_redact() {
+    use alloc::string::ToString;
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Database {
+        password: u32,
+        port: u16,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        database: Database,
+    }
+
+    let options = Options::basic()
+        .collect_errors(true)
+        .redact_keys(&["*_PASSWORD"]);
+
+    let de = Deserializer::from_str_vars([("DATABASE_PASSWORD", "42"), ("DATABASE_PORT", "5432")])
+        .with_options(options);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.database.password, 42);
+    assert_eq!(config.database.port, 5432);
+
+    let de = Deserializer::from_vars([
+        ("DATABASE_PASSWORD".to_owned(), "hunter2".to_owned()),
+        ("DATABASE_PORT".to_owned(), "http".to_owned()),
+    ])
+    .with_options(options);
+
+    let message = Config::deserialize(de).unwrap_err().to_string();
+    assert!(!message.contains("hunter2"));
+    assert!(message.contains("<redacted>"));
+    assert!(message.contains("\"http\""));
+
+    #[derive(Debug)]
+    struct Token;
+
+    impl<'de> Deserialize<'de> for Token {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: de::Deserializer<'de>,
+        {
+            let token = String::deserialize(deserializer)?;
+            Err(de::Error::custom(format!("bad token {}", token)))
+        }
+    }
+
+    let de = Deserializer::from_vars([("API_TOKEN".to_owned(), "hunter2".to_owned())])
+        .with_options(Options::basic().redact_keys(&["*_TOKEN"]));
+    let err = alloc::collections::BTreeMap::<String, Token>::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Custom);
+    assert_eq!(err.var(), Some("API_TOKEN"));
+    assert_eq!(err.to_string(), "API_TOKEN (API_TOKEN): <redacted>");
+}
+
+#[test]
+fn test_deny_unknown_vars() {
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Database {
+        url: String,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        #[serde(default)]
+        port: u16,
+        database: Database,
+    }
+
+    let de = Deserializer::from_str_vars([("DATABASE_URL", "postgres://"), ("PORT", "80")])
+        .with_options(Options::basic().deny_unknown_vars(true));
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.port, 80);
+    assert_eq!(config.database.url, "postgres://");
+
+    let de = Deserializer::from_vars([
+        ("DATABASE_URL".to_owned(), "postgres://".to_owned()),
+        ("DATABSE_URL".to_owned(), "mysql://".to_owned()),
+        ("DATABASE_ULR".to_owned(), "sqlite://".to_owned()),
+        ("PORTS".to_owned(), "80".to_owned()),
+    ])
+    .with_options(
+        Options::basic()
+            .collect_errors(true)
+            .deny_unknown_vars(true),
+    );
+
+    let err = Config::deserialize(de).unwrap_err();
+    let mut suggestions = err
+        .errors()
+        .iter()
+        .map(|err| {
+            assert_eq!(err.kind(), ErrorKind::UnknownVar);
+            (err.var().unwrap(), err.suggestion().unwrap())
+        })
+        .collect::<Vec<_>>();
+    suggestions.sort();
+    assert_eq!(
+        suggestions,
+        [
+            ("DATABASE_ULR", "DATABASE_URL"),
+            ("DATABSE_URL", "DATABASE_URL"),
+            ("PORTS", "PORT"),
+        ]
+    );
+
+    // Vars nested under scalar field are shadowed by its value.
+    let de = Deserializer::from_str_vars([
+        ("PORT_TYPO", "1"),
+        ("PORT", "80"),
+        ("DATABASE_URL", "postgres://"),
+        ("DATABASE_URL_TYPO", "2"),
+    ])
+    .with_options(
+        Options::basic()
+            .collect_errors(true)
+            .deny_unknown_vars(true),
+    );
+    let err = Config::deserialize(de).unwrap_err();
+    let mut unknown = err
+        .errors()
+        .iter()
+        .map(|err| {
+            assert_eq!(err.kind(), ErrorKind::UnknownVar);
+            err.var().unwrap()
+        })
+        .collect::<Vec<_>>();
+    unknown.sort();
+    assert_eq!(unknown, ["DATABASE_URL_TYPO", "PORT_TYPO"]);
+}
+
+#[test]
+fn test_ignore_unknown() {
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        port: u16,
+    }
+
+    let de = Deserializer::from_vars([
+        ("PORT".to_owned(), "80".to_owned()),
+        ("STRAY".to_owned(), "\"foo\"bar".to_owned()),
+        ("PORT_STRAY".to_owned(), "\"foo\"bar".to_owned()),
+    ]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.port, 80);
+}
+
+#[test]
+fn test_indexed_seq() {
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, serde_derive::Deserialize)]
+    struct Server {
+        host: String,
+        #[serde(default)]
+        port: u16,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        servers: Vec<Server>,
+        tags: Vec<String>,
+    }
+
+    let de = Deserializer::from_vars([
+        ("SERVERS_10_HOST".to_owned(), "c".to_owned()),
+        ("SERVERS_0_HOST".to_owned(), "a".to_owned()),
+        ("SERVERS_0_PORT".to_owned(), "1".to_owned()),
+        ("SERVERS_1_HOST".to_owned(), "b".to_owned()),
+        ("TAGS_1".to_owned(), "y".to_owned()),
+        ("TAGS_0".to_owned(), "x".to_owned()),
+    ]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(
+        config.servers,
+        [
+            Server {
+                host: "a".to_owned(),
+                port: 1
+            },
+            Server {
+                host: "b".to_owned(),
+                port: 0
+            },
+            Server {
+                host: "c".to_owned(),
+                port: 0
+            },
+        ]
+    );
+    assert_eq!(config.tags, ["x", "y"]);
+
+    let de = Deserializer::from_vars([
+        ("SERVERS_0_HOST".to_owned(), "a".to_owned()),
+        ("SERVERS_1_PORT".to_owned(), "x".to_owned()),
+        ("TAGS".to_owned(), "x".to_owned()),
+    ]);
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.var(), Some("SERVERS_1_PORT"));
+    assert_eq!(err.path(), Some("servers.1.port"));
+
+    let vars = [
+        ("SERVERS_0_HOST", "a"),
+        ("TAGS_0", "x"),
+        ("TAGS_00", "y"),
+        ("TAGS_+1", "z"),
+        ("TAGS_5", "w"),
+    ];
+    let config = Config::deserialize(Deserializer::from_str_vars(vars)).unwrap();
+    assert_eq!(config.tags, ["x", "w"]);
+
+    let de = Deserializer::from_str_vars(vars).with_options(
+        Options::basic()
+            .collect_errors(true)
+            .deny_unknown_vars(true),
+    );
+    let err = Config::deserialize(de).unwrap_err();
+    let mut unknown = err
+        .errors()
+        .iter()
+        .map(|err| {
+            assert_eq!(err.kind(), ErrorKind::UnknownVar);
+            err.var().unwrap()
+        })
+        .collect::<Vec<_>>();
+    unknown.sort();
+    assert_eq!(unknown, ["TAGS_+1", "TAGS_00"]);
+
+    let de =
+        Deserializer::from_str_vars([("SERVERS_0_HOST", "a"), ("TAGS_0", "x"), ("tags_0", "y")])
+            .with_options(
+                Options::basic()
+                    .key_case(KeyCase::Insensitive)
+                    .conflicts(Conflicts::Deny),
+            );
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Conflict);
+    assert_eq!(err.var(), Some("tags_0"));
+}
+
+#[test]
+fn test_keyed_map() {
+    use alloc::collections::BTreeMap;
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct DbConfig {
+        url: String,
+        #[serde(default)]
+        pool: u32,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        databases: BTreeMap<String, DbConfig>,
+        limits: BTreeMap<String, u32>,
+    }
+
+    let de = Deserializer::from_vars([
+        (
+            "DATABASES_PRIMARY_URL".to_owned(),
+            "postgres://a".to_owned(),
+        ),
+        (
+            "DATABASES_REPLICA_URL".to_owned(),
+            "postgres://b".to_owned(),
+        ),
+        ("DATABASES_PRIMARY_POOL".to_owned(), "10".to_owned()),
+        ("LIMITS_MAX_CONNECTIONS".to_owned(), "100".to_owned()),
+    ]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.databases.len(), 2);
+    assert_eq!(config.databases["PRIMARY"].url, "postgres://a");
+    assert_eq!(config.databases["PRIMARY"].pool, 10);
+    assert_eq!(config.databases["REPLICA"].url, "postgres://b");
+    assert_eq!(config.databases["REPLICA"].pool, 0);
+    assert_eq!(config.limits["MAX_CONNECTIONS"], 100);
+
+    let de = Deserializer::from_vars([("DATABASES_PRIMARY_POOL".to_owned(), "10".to_owned())]);
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.var(), Some("DATABASES_PRIMARY_URL"));
+    assert_eq!(err.path(), Some("databases.PRIMARY.url"));
+}
+
+#[test]
+fn test_nesting_separator() {
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Db {
+        url: String,
+        max_connections: u32,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        max: u32,
+        max_connections: u32,
+        db: Db,
+        hosts: Vec<String>,
+    }
+
+    let de = Deserializer::from_vars([
+        ("MAX".to_owned(), "1".to_owned()),
+        ("MAX_CONNECTIONS".to_owned(), "2".to_owned()),
+        ("DB__URL".to_owned(), "postgres://".to_owned()),
+        ("DB__MAX_CONNECTIONS".to_owned(), "3".to_owned()),
+        ("HOSTS__0".to_owned(), "a".to_owned()),
+        ("HOSTS__1".to_owned(), "b".to_owned()),
+    ])
+    .with_options(Options::basic().nesting_separator("__"));
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.max, 1);
+    assert_eq!(config.max_connections, 2);
+    assert_eq!(config.db.url, "postgres://");
+    assert_eq!(config.db.max_connections, 3);
+    assert_eq!(config.hosts, ["a", "b"]);
+
+    let de = Deserializer::from_vars([
+        ("MAX".to_owned(), "1".to_owned()),
+        ("MAX_CONNECTIONS".to_owned(), "2".to_owned()),
+        ("DB.URL".to_owned(), "postgres://".to_owned()),
+        ("DB.MAX_CONNECTIONS".to_owned(), "x".to_owned()),
+        ("HOSTS".to_owned(), "a".to_owned()),
+    ])
+    .with_options(Options::basic().nesting_separator("."));
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.var(), Some("DB.MAX_CONNECTIONS"));
+    assert_eq!(err.path(), Some("db.max_connections"));
+}
+
+#[test]
+#[should_panic(expected = "nesting separator must not be empty")]
+fn test_empty_nesting_separator() {
+    let _ = Options::basic().nesting_separator("");
+}
+
+#[test]
+fn test_conflicts() {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Pool {
+        size: u32,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Db {
+        #[serde(default)]
+        host: String,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        db: Option<Db>,
+        db_pool: Pool,
+    }
+
+    static VARS: [(&str, &str); 3] = [("DB_POOL_SIZE", "1"), ("DB", ""), ("DB_HOST", "localhost")];
+
+    let de = Deserializer::from_str_vars(VARS).with_options(
+        Options::basic()
+            .conflicts(Conflicts::Deny)
+            .collect_errors(true),
+    );
+    let err = Config::deserialize(de).unwrap_err();
+    let mut conflicts = err
+        .errors()
+        .iter()
+        .map(|err| {
+            assert_eq!(err.kind(), ErrorKind::Conflict);
+            err.var().unwrap()
+        })
+        .collect::<Vec<_>>();
+    conflicts.sort();
+    assert_eq!(conflicts, ["DB_HOST", "DB_POOL_SIZE"]);
+
+    static WARNINGS: AtomicUsize = AtomicUsize::new(0);
+    let de = Deserializer::from_str_vars(VARS).with_options(Options::basic().conflicts(
+        Conflicts::Warn(|_| {
+            WARNINGS.fetch_add(1, Ordering::Relaxed);
+        }),
+    ));
+    let config = Config::deserialize(de).unwrap();
+    // Value of `DB` shadows `DB_HOST`.
+    assert_eq!(config.db.unwrap().host, "");
+    assert_eq!(config.db_pool.size, 1);
+    assert_eq!(WARNINGS.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn test_options_builder() {
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        port: u16,
+        debug: bool,
+    }
+
+    let mut de = Deserializer::from_vars([
+        ("port".to_owned(), "80".to_owned()),
+        ("debug".to_owned(), "true".to_owned()),
+    ]);
+    let options = de.options_mut();
+    *options = options.ident_upper(false);
+
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.port, 80);
+    assert!(config.debug);
+
+    let de = Deserializer::from_vars([
+        ("PORT".to_owned(), "80".to_owned()),
+        ("DEBUG".to_owned(), "yes".to_owned()),
+    ])
+    .with_options(Options::new(BasicParser).parser(FromStrParser));
+    // `yes` is a boolean for `BasicParser`, but not for `FromStrParser`.
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidValue);
+    assert_eq!(err.var(), Some("DEBUG"));
+}
+
+#[test]
+fn test_key_case() {
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct Config {
+        pool_size: u32,
+        max_idle_time: u32,
+    }
+
+    let de = Deserializer::from_vars([
+        ("POOL_SIZE".to_owned(), "4".to_owned()),
+        ("MAX_IDLE_TIME".to_owned(), "30".to_owned()),
+    ])
+    .with_options(Options::basic().key_case(KeyCase::ScreamingSnake));
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.pool_size, 4);
+    assert_eq!(config.max_idle_time, 30);
+
+    let de = Deserializer::from_vars([("POOL_SIZE".to_owned(), "4".to_owned())])
+        .with_options(Options::basic().key_case(KeyCase::ScreamingSnake));
+    let err = Config::deserialize(de).unwrap_err();
+    assert!(err.to_string().contains("MAX_IDLE_TIME"), "{err}");
+
+    let de = Deserializer::from_vars([
+        ("pool-size".to_owned(), "4".to_owned()),
+        ("Max.Idle_Time".to_owned(), "30".to_owned()),
+    ])
+    .with_options(Options::basic().key_case(KeyCase::Normalized));
+    #[derive(serde_derive::Deserialize)]
+    struct Snake {
+        pool_size: u32,
+        max_idle_time: u32,
+    }
+    let config = Snake::deserialize(de).unwrap();
+    assert_eq!(config.pool_size, 4);
+    assert_eq!(config.max_idle_time, 30);
+
+    #[derive(serde_derive::Deserialize)]
+    struct Db {
+        url: String,
+        pool_size: u32,
+    }
+
+    #[derive(serde_derive::Deserialize)]
+    struct Nested {
+        db: Db,
+        hosts: Vec<String>,
+    }
+
+    let de = Deserializer::from_str_vars([
+        ("db-url", "postgres://"),
+        ("DB.POOL-SIZE", "4"),
+        ("hosts.0", "a"),
+        ("HOSTS-1", "b"),
+    ])
+    .with_options(Options::basic().key_case(KeyCase::Normalized));
+    let config = Nested::deserialize(de).unwrap();
+    assert_eq!(config.db.url, "postgres://");
+    assert_eq!(config.db.pool_size, 4);
+    assert_eq!(config.hosts, ["a", "b"]);
+}
+
+#[test]
+fn test_map_keys() {
+    use alloc::collections::BTreeMap;
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        labels: BTreeMap<String, String>,
+    }
+
+    static VARS: [(&str, &str); 2] = [("LABELS_TEAM_NAME", "core"), ("LABELS_ENV", "prod")];
+
+    let de = Deserializer::from_str_vars(VARS);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.labels["TEAM_NAME"], "core");
+
+    let de =
+        Deserializer::from_str_vars(VARS).with_options(Options::basic().map_keys(MapKeys::Kebab));
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.labels["team-name"], "core");
+    assert_eq!(config.labels["env"], "prod");
+
+    let de =
+        Deserializer::from_str_vars(VARS).with_options(Options::basic().map_keys(MapKeys::Dotted));
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.labels["team.name"], "core");
+}
+
+#[test]
+fn test_empty_as_none() {
+    use alloc::collections::BTreeMap;
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        proxy_url: Option<String>,
+        port: Option<u16>,
+        timeout: Option<u32>,
+        labels: BTreeMap<String, Option<u8>>,
+    }
+
+    static VARS: [(&str, &str); 4] = [
+        ("PROXY_URL", ""),
+        ("PORT", "  "),
+        ("TIMEOUT", "Null"),
+        ("LABELS_A", ""),
+    ];
+
+    let de = Deserializer::from_str_vars(VARS);
+    assert!(Config::deserialize(de).is_err());
+
+    let de = Deserializer::from_str_vars(VARS).with_options(
+        Options::basic()
+            .empty_as_none(true)
+            .none_literals(&["null", "none"]),
+    );
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.proxy_url, None);
+    assert_eq!(config.port, None);
+    assert_eq!(config.timeout, None);
+    assert_eq!(config.labels["A"], None);
+}
+
+#[test]
+fn test_borrowed() {
+    use alloc::borrow::Cow;
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config<'a> {
+        host: &'a str,
+        #[serde(borrow)]
+        name: Cow<'a, str>,
+        #[serde(borrow)]
+        quoted: Cow<'a, str>,
+        db: Db<'a>,
+        port: u16,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Db<'a> {
+        url: &'a str,
+    }
+
+    let vars = [
+        ("HOST", "localhost"),
+        ("NAME", "app"),
+        ("QUOTED", "\"a\\tb\""),
+        ("DB_URL", "postgres://db"),
+        ("PORT", "80"),
+    ];
+    let config = Config::deserialize(BorrowedDeserializer::from_vars(&vars)).unwrap();
+    assert_eq!(config.host, "localhost");
+    assert!(matches!(config.name, Cow::Borrowed("app")));
+    assert!(matches!(config.quoted, Cow::Owned(ref s) if s == "a\tb"));
+    assert_eq!(config.db.url, "postgres://db");
+    assert_eq!(config.port, 80);
+}
+
+#[test]
+fn test_from_static_vars() {
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        name: String,
+        port: u16,
+    }
+
+    static VARS: [(&str, &str); 2] = [("NAME", "firmware"), ("PORT", "8080")];
+
+    let config = Config::deserialize(Deserializer::from_str_vars(VARS)).unwrap();
+    assert_eq!(config.name, "firmware");
+    assert_eq!(config.port, 8080);
+}
+
+#[cfg(all(feature = "std", unix))]
+#[test]
+fn test_os_vars() {
+    use serde::Deserialize;
+    use std::{ffi::OsString, os::unix::ffi::OsStringExt, path::PathBuf};
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        data_dir: OsString,
+        #[serde(deserialize_with = "os_path")]
+        log_path: PathBuf,
+        name: String,
+        key: Bytes,
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Bytes(Vec<u8>);
+
+    impl<'de> Deserialize<'de> for Bytes {
+        fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct BytesVisitor;
+
+            impl Visitor<'_> for BytesVisitor {
+                type Value = Bytes;
+
+                fn expecting(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+                    f.write_str("bytes")
+                }
+
+                fn visit_bytes<E>(self, v: &[u8]) -> Result<Bytes, E> {
+                    Ok(Bytes(v.to_vec()))
+                }
+
+                fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Bytes, E> {
+                    Ok(Bytes(v))
+                }
+            }
+
+            deserializer.deserialize_bytes(BytesVisitor)
+        }
+    }
+
+    // Stray var with invalid name is skipped by default.
+    let de = Deserializer::from_os_vars(
+        "APP_",
+        [
+            (
+                OsString::from("APP_DATA_DIR"),
+                OsString::from_vec(b"/srv/d\xffta".to_vec()),
+            ),
+            (
+                OsString::from("APP_LOG_PATH"),
+                OsString::from_vec(b"/var/l\xffg".to_vec()),
+            ),
+            (OsString::from("APP_NAME"), OsString::from("app")),
+            (OsString::from("APP_KEY"), OsString::from("aGk")),
+            (
+                OsString::from_vec(b"APP_\xff".to_vec()),
+                OsString::from("x"),
+            ),
+            (OsString::from("OTHER"), OsString::from("y")),
+        ],
+    );
+    assert_eq!(de.skipped_vars(), ["APP_\u{fffd}"]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.data_dir.into_vec(), b"/srv/d\xffta");
+    assert_eq!(config.log_path.into_os_string().into_vec(), b"/var/l\xffg");
+    assert_eq!(config.name, "app");
+    // Bytes are taken as is regardless of validity of UTF-8.
+    assert_eq!(config.key, Bytes(b"aGk".to_vec()));
+
+    // By default only vars that would match a field are denied.
+    let vars = [
+        (OsString::from("DATA_DIR"), OsString::from("/srv")),
+        (OsString::from("LOG_PATH"), OsString::from("/var/log")),
+        (OsString::from("NAME"), OsString::from("app")),
+        (OsString::from("KEY"), OsString::from("aGk")),
+        (OsString::from_vec(b"KEY\xff".to_vec()), OsString::from("x")),
+        (
+            OsString::from_vec(b"NAME_\xff".to_vec()),
+            OsString::from("x"),
+        ),
+    ];
+    let err = Config::deserialize(Deserializer::from_os_vars("", vars.clone())).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidUtf8);
+    assert_eq!(err.var(), Some("NAME_\u{fffd}"));
+
+    let de = Deserializer::from_os_vars("", vars)
+        .with_options(Options::basic().skipped_vars(SkippedVars::Ignore));
+    assert_eq!(de.skipped_vars(), ["KEY\u{fffd}", "NAME_\u{fffd}"]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.name, "app");
+
+    let de = Deserializer::from_os_vars(
+        "",
+        [
+            (OsString::from("DATA_DIR"), OsString::from("/srv")),
+            (OsString::from("LOG_PATH"), OsString::from("/var/log")),
+            (OsString::from("NAME"), OsString::from_vec(b"\xff".to_vec())),
+            (OsString::from("KEY"), OsString::from("aGk")),
+        ],
+    );
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidUtf8);
+    assert_eq!(err.var(), Some("NAME"));
+}
@@ -0,0 +1,172 @@
+//! Parser for `.env` files.
+//!
+//! Supports comments, `export` prefixes, unquoted values,
+//! single-quoted literal values and double-quoted values with escapes.
+//! Quoted values may span multiple lines.
+
+use alloc::{borrow::ToOwned, format, string::String, vec::Vec};
+
+use crate::{unescape::unescape, Error, ErrorKind};
+
+/// Parses contents of a dotenv file into list of key-value pairs.
+///
+/// When key is repeated, the last value wins.
+pub(crate) fn parse(input: &str) -> Result<Vec<(String, String)>, Error> {
+    let mut vars = Vec::<(String, String)>::new();
+    let mut rest = input;
+
+    loop {
+        rest = rest.trim_start();
+        if rest.is_empty() {
+            break;
+        }
+
+        if rest.starts_with('#') {
+            rest = skip_line(rest);
+            continue;
+        }
+
+        if let Some(tail) = rest.strip_prefix("export") {
+            if tail.starts_with([' ', '\t']) {
+                rest = tail.trim_start_matches([' ', '\t']);
+            }
+        }
+
+        let key_len = rest.find(|c: char| !is_key_char(c)).unwrap_or(rest.len());
+        let (key, tail) = rest.split_at(key_len);
+        if key.is_empty() {
+            return Err(syntax_error(input, rest, "expected variable name"));
+        }
+
+        let tail = tail.trim_start_matches([' ', '\t']);
+        let Some(tail) = tail.strip_prefix('=') else {
+            return Err(syntax_error(
+                input,
+                tail,
+                "expected `=` after variable name",
+            ));
+        };
+        let tail = tail.trim_start_matches([' ', '\t']);
+
+        let (value, tail) = if let Some(quoted) = tail.strip_prefix('"') {
+            let (value, after) = unescape(quoted).map_err(|_| {
+                syntax_error(
+                    input,
+                    tail,
+                    "invalid escape sequence in double-quoted value",
+                )
+            })?;
+            let Some(after) = after else {
+                return Err(syntax_error(
+                    input,
+                    tail,
+                    "unterminated double-quoted value",
+                ));
+            };
+            (value, end_of_quoted(input, after)?)
+        } else if let Some(quoted) = tail.strip_prefix('\'') {
+            let Some((value, after)) = quoted.split_once('\'') else {
+                return Err(syntax_error(
+                    input,
+                    tail,
+                    "unterminated single-quoted value",
+                ));
+            };
+            (value.to_owned(), end_of_quoted(input, after)?)
+        } else {
+            let line_len = tail.find('\n').unwrap_or(tail.len());
+            let (line, after) = tail.split_at(line_len);
+            let value = match line.find(" #").or_else(|| line.find("\t#")) {
+                Some(comment) => &line[..comment],
+                None => line,
+            };
+            (value.trim_end().to_owned(), after)
+        };
+
+        match vars.iter().position(|(ident, _)| ident == key) {
+            Some(index) => vars[index].1 = value,
+            None => vars.push((key.to_owned(), value)),
+        }
+
+        rest = tail;
+    }
+
+    Ok(vars)
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')
+}
+
+fn skip_line(s: &str) -> &str {
+    match s.split_once('\n') {
+        Some((_, tail)) => tail,
+        None => "",
+    }
+}
+
+/// Checks that only whitespace and a comment follow closing quote on the same line.
+fn end_of_quoted<'a>(input: &str, after: &'a str) -> Result<&'a str, Error> {
+    let after = after.trim_start_matches([' ', '\t', '\r']);
+    if after.is_empty() || after.starts_with('\n') || after.starts_with('#') {
+        Ok(skip_line(after))
+    } else {
+        Err(syntax_error(
+            input,
+            after,
+            "unexpected characters after closing quote",
+        ))
+    }
+}
+
+/// Builds an error pointing at the start of `at`, which must be a suffix of `input`.
+fn syntax_error(input: &str, at: &str, msg: &str) -> Error {
+    let offset = input.len() - at.len();
+    let before = &input[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |n| n + 1);
+    let column = before[line_start..].chars().count() + 1;
+
+    Error::new(
+        ErrorKind::Syntax,
+        format!("{} at line {}, column {}", msg, line, column),
+    )
+}
+
+#[test]
+fn test_parse() {
+    use alloc::string::ToString;
+
+    let vars = parse(
+        r#"
+# comment
+export A=1
+B = two words # comment
+C='single \n "quoted"'
+D="double\tquoted" # comment
+E="multi
+line"
+A=3
+"#,
+    )
+    .unwrap();
+
+    let get = |key: &str| {
+        vars.iter()
+            .find(|(ident, _)| ident == key)
+            .map(|(_, value)| value.as_str())
+    };
+
+    assert_eq!(vars.len(), 5);
+    assert_eq!(get("A"), Some("3"));
+    assert_eq!(get("B"), Some("two words"));
+    assert_eq!(get("C"), Some(r#"single \n "quoted""#));
+    assert_eq!(get("D"), Some("double\tquoted"));
+    assert_eq!(get("E"), Some("multi\nline"));
+
+    let err = parse("A=1\nB=\"x\" y\n").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unexpected characters after closing quote at line 2, column 7"
+    );
+}
@@ -0,0 +1,519 @@
+use core::fmt;
+
+use alloc::{
+    borrow::ToOwned,
+    boxed::Box,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Kind of the error.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Required field has no env var.
+    MissingField,
+
+    /// Value can't be parsed as requested type.
+    InvalidValue,
+
+    /// Value contains invalid escape sequence or unterminated quoted string.
+    InvalidEscape,
+
+    /// Value is not a valid base64 sequence.
+    InvalidBase64,
+
+    /// Env var name or value is not valid UTF-8.
+    InvalidUtf8,
+
+    /// Value names unknown enum variant.
+    UnknownVariant,
+
+    /// Struct does not have a field with this name.
+    UnknownField,
+
+    /// Env var does not map to any field.
+    /// Reported only when unknown vars are denied.
+    UnknownVar,
+
+    /// Env var conflicts with other vars.
+    /// Reported only when conflicts are denied.
+    Conflict,
+
+    /// Value was rejected by JSON parser.
+    Json,
+
+    /// Value was rejected by TOML parser.
+    Toml,
+
+    /// Dotenv file is malformed.
+    Syntax,
+
+    /// Failed to read the source of env vars.
+    Io,
+
+    /// Several errors were collected.
+    /// See [`Error::errors`].
+    Multiple,
+
+    /// Any other error.
+    Custom,
+}
+
+#[derive(Debug)]
+pub struct Error {
+    inner: Box<ErrorImpl>,
+}
+
+#[derive(Debug)]
+struct ErrorImpl {
+    kind: ErrorKind,
+    message: String,
+
+    /// Rendered offending value included in the message.
+    unexpected: Option<String>,
+
+    /// Field that is missing, if this is a missing field error.
+    missing_field: Option<&'static str>,
+
+    /// Full name of the env var that caused the error.
+    var: Option<String>,
+
+    /// Path of fields and keys leading to the value that caused the error.
+    path: Option<String>,
+
+    /// Valid env var name similar to the unknown one.
+    suggestion: Option<String>,
+
+    /// Collected errors if this is an aggregated error.
+    errors: Vec<Error>,
+
+    /// Upstream error.
+    #[cfg(feature = "std")]
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl Error {
+    pub(crate) fn new(kind: ErrorKind, message: String) -> Self {
+        Error {
+            inner: Box::new(ErrorImpl {
+                kind,
+                message,
+                unexpected: None,
+                missing_field: None,
+                var: None,
+                path: None,
+                suggestion: None,
+                errors: Vec::new(),
+                #[cfg(feature = "std")]
+                source: None,
+            }),
+        }
+    }
+
+    /// Returns error with upstream error kept as source.
+    #[cfg(feature = "std")]
+    pub(crate) fn with_source<E>(kind: ErrorKind, source: E) -> Self
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut err = Error::new(kind, source.to_string());
+        err.inner.source = Some(Box::new(source));
+        err
+    }
+
+    /// Returns error for env var that does not map to any field.
+    pub(crate) fn unknown_var(var: &str, suggestion: Option<String>) -> Self {
+        let mut err = Error::new(ErrorKind::UnknownVar, "unknown env var".to_owned());
+        err.inner.var = Some(var.to_owned());
+        err.inner.suggestion = suggestion;
+        err
+    }
+
+    /// Returns error for env var with name that is not valid UTF-8.
+    pub(crate) fn invalid_utf8_name(var: &str) -> Self {
+        let mut err = Error::new(
+            ErrorKind::InvalidUtf8,
+            "env var name is not valid UTF-8".to_owned(),
+        );
+        err.inner.var = Some(var.to_owned());
+        err
+    }
+
+    /// Returns error for env var value that is not valid UTF-8,
+    /// when string is requested.
+    #[cfg(feature = "std")]
+    pub(crate) fn invalid_utf8_value() -> Self {
+        Error::new(
+            ErrorKind::InvalidUtf8,
+            "env var value is not valid UTF-8".to_owned(),
+        )
+    }
+
+    /// Returns error for env var that conflicts with other vars.
+    pub(crate) fn conflict(var: &str, message: String) -> Self {
+        let mut err = Error::new(ErrorKind::Conflict, message);
+        err.inner.var = Some(var.to_owned());
+        err
+    }
+
+    /// Combines collected errors into one.
+    pub(crate) fn collect(mut errors: Vec<Error>) -> Self {
+        if errors.len() == 1 {
+            return errors.pop().unwrap();
+        }
+
+        let mut err = Error::new(ErrorKind::Multiple, String::new());
+        err.inner.errors = errors;
+        err
+    }
+
+    /// Replaces kind of the error.
+    pub(crate) fn with_kind(mut self, kind: ErrorKind) -> Self {
+        self.inner.kind = kind;
+        self
+    }
+
+    /// Returns kind of the error.
+    pub fn kind(&self) -> ErrorKind {
+        self.inner.kind
+    }
+
+    /// Returns full name of the env var that caused the error.
+    /// For example `DATABASE_POOL_SIZE`.
+    pub fn var(&self) -> Option<&str> {
+        self.inner.var.as_deref()
+    }
+
+    /// Returns path of fields and keys leading to the value that caused the error.
+    /// For example `database.pool.size`.
+    pub fn path(&self) -> Option<&str> {
+        self.inner.path.as_deref()
+    }
+
+    /// Returns valid env var name similar to the unknown one.
+    pub fn suggestion(&self) -> Option<&str> {
+        self.inner.suggestion.as_deref()
+    }
+
+    /// Returns all collected errors if this is an aggregated error.
+    /// Otherwise returns empty slice.
+    pub fn errors(&self) -> &[Error] {
+        &self.inner.errors
+    }
+
+    /// Returns name of the missing field if location of the error is not yet known.
+    pub(crate) fn unlocated_missing_field(&self) -> Option<&'static str> {
+        match self.inner.var {
+            None => self.inner.missing_field,
+            Some(_) => None,
+        }
+    }
+
+    /// Prepends env var name and field to the location of the error.
+    pub(crate) fn with_key(mut self, var: &str, field: &str, separator: &str) -> Self {
+        self.locate(var, field, separator);
+        self
+    }
+
+    /// Prepends env var name and field to the location of the error in place.
+    /// Env var names are joined with `separator`.
+    pub(crate) fn locate(&mut self, var: &str, field: &str, separator: &str) {
+        self.inner.var = Some(match self.inner.var.take() {
+            None => var.to_owned(),
+            Some(tail) => format!("{}{}{}", var, separator, tail),
+        });
+        if let Some(suggestion) = &mut self.inner.suggestion {
+            suggestion.insert_str(0, separator);
+            suggestion.insert_str(0, var);
+        }
+        self.inner.path = Some(match self.inner.path.take() {
+            None => field.to_owned(),
+            Some(tail) => format!("{}.{}", field, tail),
+        });
+    }
+
+    /// Removes offending value from the message.
+    pub(crate) fn redact(&mut self) {
+        match self.inner.kind {
+            ErrorKind::Json => self.inner.message = "value rejected by JSON parser".to_owned(),
+            ErrorKind::Toml => self.inner.message = "value rejected by TOML parser".to_owned(),
+            // Custom messages may embed the value anywhere.
+            ErrorKind::Custom => self.inner.message = REDACTED.to_owned(),
+            _ => {
+                if let Some(unexpected) = self.inner.unexpected.take() {
+                    self.inner.message = self.inner.message.replacen(&unexpected, REDACTED, 1);
+                }
+            }
+        }
+
+        #[cfg(feature = "std")]
+        {
+            self.inner.source = None;
+        }
+    }
+
+    /// Redacts this error and collected errors if `all` is set
+    /// or env var name matches one of the `patterns`.
+    pub(crate) fn redact_matching(mut self, all: bool, patterns: &[&str]) -> Self {
+        let matches = |err: &Error| {
+            all || err
+                .var()
+                .is_some_and(|var| patterns.iter().any(|pattern| matches_pattern(pattern, var)))
+        };
+
+        if matches(&self) {
+            self.redact();
+        }
+        for err in &mut self.inner.errors {
+            if matches(err) {
+                err.redact();
+            }
+        }
+        self
+    }
+
+    /// Prepends prefix stripped from env var names.
+    pub(crate) fn with_prefix(mut self, prefix: &str) -> Self {
+        if prefix.is_empty() {
+            return self;
+        }
+        if let Some(var) = &mut self.inner.var {
+            var.insert_str(0, prefix);
+        }
+        if let Some(suggestion) = &mut self.inner.suggestion {
+            suggestion.insert_str(0, prefix);
+        }
+        self.inner.errors = core::mem::take(&mut self.inner.errors)
+            .into_iter()
+            .map(|err| err.with_prefix(prefix))
+            .collect();
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.inner.var, &self.inner.path) {
+            (Some(var), Some(path)) => write!(f, "{} ({}): ", var, path)?,
+            (Some(var), None) => write!(f, "{}: ", var)?,
+            _ => {}
+        }
+        if self.inner.kind == ErrorKind::Multiple {
+            write!(f, "{} errors:", self.inner.errors.len())?;
+            for err in &self.inner.errors {
+                write!(f, "\n  {}", err)?;
+            }
+            return Ok(());
+        }
+        f.write_str(&self.inner.message)?;
+        if let Some(suggestion) = &self.inner.suggestion {
+            write!(f, ", did you mean `{}`?", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match &self.inner.source {
+            Some(source) => Some(&**source),
+            None => None,
+        }
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Error::new(ErrorKind::Custom, msg.to_string())
+    }
+}
+
+impl serde::de::Error for Error {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Error::new(ErrorKind::Custom, msg.to_string())
+    }
+
+    fn invalid_type(unexp: serde::de::Unexpected, exp: &dyn serde::de::Expected) -> Self {
+        let unexpected = unexp.to_string();
+        let mut err = Error::new(
+            ErrorKind::InvalidValue,
+            format!("invalid type: {}, expected {}", unexpected, exp),
+        );
+        err.inner.unexpected = Some(unexpected);
+        err
+    }
+
+    fn invalid_value(unexp: serde::de::Unexpected, exp: &dyn serde::de::Expected) -> Self {
+        let unexpected = unexp.to_string();
+        let mut err = Error::new(
+            ErrorKind::InvalidValue,
+            format!("invalid value: {}, expected {}", unexpected, exp),
+        );
+        err.inner.unexpected = Some(unexpected);
+        err
+    }
+
+    fn invalid_length(len: usize, exp: &dyn serde::de::Expected) -> Self {
+        Error::new(
+            ErrorKind::InvalidValue,
+            format!("invalid length {}, expected {}", len, exp),
+        )
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        let unexpected = format!("`{}`", variant);
+        let message = match expected {
+            [] => format!("unknown variant {}, there are no variants", unexpected),
+            _ => format!(
+                "unknown variant {}, expected {}",
+                unexpected,
+                OneOf(expected)
+            ),
+        };
+        let mut err = Error::new(ErrorKind::UnknownVariant, message);
+        err.inner.unexpected = Some(unexpected);
+        err
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        let message = match expected {
+            [] => format!("unknown field `{}`, there are no fields", field),
+            _ => format!("unknown field `{}`, expected {}", field, OneOf(expected)),
+        };
+        Error::new(ErrorKind::UnknownField, message)
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        let mut err = Error::new(
+            ErrorKind::MissingField,
+            format!("missing field `{}`", field),
+        );
+        err.inner.missing_field = Some(field);
+        err
+    }
+}
+
+/// Placeholder for redacted values.
+const REDACTED: &str = "<redacted>";
+
+/// Matches env var name against a pattern where `*` matches any sequence of characters.
+/// Comparison is ASCII case-insensitive.
+fn matches_pattern(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern.eq_ignore_ascii_case(name),
+        Some((head, tail)) => {
+            let Some(rest) = name
+                .get(..head.len())
+                .filter(|start| start.eq_ignore_ascii_case(head))
+                .map(|_| &name[head.len()..])
+            else {
+                return false;
+            };
+
+            rest.char_indices()
+                .map(|(index, _)| index)
+                .chain(Some(rest.len()))
+                .any(|index| matches_pattern(tail, &rest[index..]))
+        }
+    }
+}
+
+/// Formats list of expected names the same way serde does.
+struct OneOf(&'static [&'static str]);
+
+impl fmt::Display for OneOf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            [] => Ok(()),
+            [only] => write!(f, "`{}`", only),
+            [first, second] => write!(f, "`{}` or `{}`", first, second),
+            [first, rest @ ..] => {
+                write!(f, "one of `{}`", first)?;
+                for name in rest {
+                    write!(f, ", `{}`", name)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[test]
+fn test_error_kind() {
+    use crate::Deserializer;
+    use serde::Deserialize;
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config {
+        port: u16,
+        name: String,
+    }
+
+    let de = Deserializer::from_str_vars([("PORT", "1"), ("NAME", "x")]);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.port, 1);
+    assert_eq!(config.name, "x");
+
+    let de = Deserializer::from_str_vars([("NAME", "x")]);
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MissingField);
+
+    let de = Deserializer::from_str_vars([("PORT", "x"), ("NAME", "x")]);
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidValue);
+
+    let de = Deserializer::from_str_vars([("PORT", "1"), ("NAME", "\"\\q\"")]);
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidEscape);
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    enum Mode {
+        Fast,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Extra<'a> {
+        mode: Option<Mode>,
+        key: Option<&'a [u8]>,
+    }
+
+    let de = Deserializer::from_str_vars([("MODE", "Fast")]);
+    let extra = Extra::deserialize(de).unwrap();
+    assert!(matches!(extra.mode, Some(Mode::Fast)));
+    assert_eq!(extra.key, None);
+
+    let de = Deserializer::from_str_vars([("MODE", "Slow")]);
+    let err = Extra::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnknownVariant);
+
+    let de = Deserializer::from_str_vars([("KEY", "not base64!")]);
+    let err = Extra::deserialize(de).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidBase64);
+
+    #[cfg(feature = "json")]
+    {
+        let de = Deserializer::from_str_vars([("PORT", "x"), ("NAME", "x")])
+            .with_options(crate::Options::json());
+        let err = Config::deserialize(de).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Json);
+        assert!(std::error::Error::source(&err).is_some());
+    }
+
+    #[cfg(feature = "toml")]
+    {
+        let de = Deserializer::from_str_vars([("PORT", "x"), ("NAME", "x")])
+            .with_options(crate::Options::toml());
+        let err = Config::deserialize(de).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Toml);
+        assert!(std::error::Error::source(&err).is_some());
+    }
+}
@@ -0,0 +1,182 @@
+use core::str::FromStr;
+
+use serde::de::{IntoDeserializer, Visitor};
+
+use crate::{parser::Parser, Error};
+
+/// Parser that uses `FromStr` to parse values
+/// into requested type.
+///
+/// Strings are taken as is, without unescaping.
+/// Sequences, maps and structs can't be parsed from a single value,
+/// enums are parsed from unit variant names.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FromStrParser;
+
+fn from_str<T>(value: &str, expected: &str) -> Result<T, Error>
+where
+    T: FromStr,
+{
+    value
+        .parse()
+        .map_err(|_| serde::de::Error::invalid_value(serde::de::Unexpected::Str(value), &expected))
+}
+
+fn unsupported<'de, V>(value: &str, visitor: V) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    Err(serde::de::Error::invalid_type(
+        serde::de::Unexpected::Str(value),
+        &visitor,
+    ))
+}
+
+macro_rules! impl_from_str {
+    ($($parse:ident $type:ident $visit:ident $expected:literal)*) => {
+        $(
+            #[inline]
+            fn $parse<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+            where
+                V: Visitor<'de>,
+            {
+                visitor.$visit(from_str::<$type>(value, $expected)?)
+            }
+        )*
+    };
+}
+
+impl Parser for FromStrParser {
+    impl_from_str! {
+        parse_bool bool visit_bool "a boolean"
+        parse_i8 i8 visit_i8 "a number"
+        parse_i16 i16 visit_i16 "a number"
+        parse_i32 i32 visit_i32 "a number"
+        parse_i64 i64 visit_i64 "a number"
+        parse_i128 i128 visit_i128 "a number"
+        parse_u8 u8 visit_u8 "a number"
+        parse_u16 u16 visit_u16 "a number"
+        parse_u32 u32 visit_u32 "a number"
+        parse_u64 u64 visit_u64 "a number"
+        parse_u128 u128 visit_u128 "a number"
+        parse_f32 f32 visit_f32 "a number"
+        parse_f64 f64 visit_f64 "a number"
+        parse_char char visit_char "a character"
+    }
+
+    fn parse_seq<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        unsupported(value, visitor)
+    }
+
+    fn parse_map<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        unsupported(value, visitor)
+    }
+
+    fn parse_enum<'de, V>(
+        self,
+        value: &str,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(value))
+    }
+
+    fn parse_struct<'de, V>(
+        self,
+        value: &str,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        unsupported(value, visitor)
+    }
+
+    fn parse_bytes<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_bytes(value.as_bytes())
+    }
+
+    fn parse_any<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(value)
+    }
+
+    fn parse_str<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_str(value)
+    }
+
+    fn parse_borrowed_str<'de, V>(self, value: &'de str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_str(value)
+    }
+}
+
+#[test]
+fn test_from_str() {
+    use alloc::{borrow::ToOwned, string::String};
+    use serde::Deserialize;
+
+    #[derive(serde_derive::Deserialize, Debug, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Mode {
+        Fast,
+        Safe,
+    }
+
+    #[derive(serde_derive::Deserialize, Debug)]
+    struct Config {
+        name: String,
+        port: u16,
+        debug: bool,
+        mode: Mode,
+        list: String,
+    }
+
+    let de = crate::Deserializer::from_vars([
+        ("NAME".to_owned(), "\"quoted\\n\"".to_owned()),
+        ("PORT".to_owned(), "8080".to_owned()),
+        ("DEBUG".to_owned(), "true".to_owned()),
+        ("MODE".to_owned(), "safe".to_owned()),
+        ("LIST".to_owned(), "a,b:c".to_owned()),
+    ])
+    .with_options(crate::Options::from_str());
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.name, "\"quoted\\n\"");
+    assert_eq!(config.port, 8080);
+    assert!(config.debug);
+    assert_eq!(config.mode, Mode::Safe);
+    assert_eq!(config.list, "a,b:c");
+
+    let de = crate::Deserializer::from_vars([
+        ("NAME".to_owned(), "x".to_owned()),
+        ("PORT".to_owned(), " 8080".to_owned()),
+        ("DEBUG".to_owned(), "yes".to_owned()),
+        ("MODE".to_owned(), "fast".to_owned()),
+        ("LIST".to_owned(), "".to_owned()),
+    ])
+    .with_options(crate::Options::from_str().collect_errors(true));
+    let err = Config::deserialize(de).unwrap_err();
+    assert_eq!(err.errors().len(), 2);
+}
@@ -0,0 +1,723 @@
+//! Deserializer that works without heap allocations.
+//!
+//! Available without `alloc` feature, for targets that have no heap.
+//! Supports structs, nested structs, options, unit enum variants and scalar values.
+//! String values are borrowed, quoted ones are unescaped into a stack buffer.
+
+use core::fmt;
+
+use serde::de::{self, value::BorrowedStrDeserializer, Visitor};
+
+use crate::{
+    unescape::{unescape_chars, EscapeError},
+    KeyCase,
+};
+
+/// Size of the stack buffer for unescaped strings.
+pub const UNESCAPE_BUFFER_SIZE: usize = 256;
+
+/// Error of deserialization without allocations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// Required field has no env var.
+    MissingField(&'static str),
+
+    /// Value can't be parsed as requested type.
+    InvalidValue,
+
+    /// Value contains invalid escape sequence or unterminated quoted string.
+    InvalidEscape,
+
+    /// Unescaped string does not fit into the stack buffer.
+    BufferOverflow,
+
+    /// Value names unknown enum variant.
+    UnknownVariant,
+
+    /// Requested type can't be deserialized without allocations,
+    /// e.g. sequences and maps.
+    Unsupported,
+
+    /// Any other error.
+    Custom,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MissingField(field) => write!(f, "missing env var for field `{}`", field),
+            Error::InvalidValue => f.write_str("invalid value"),
+            Error::InvalidEscape => f.write_str("invalid escaped string"),
+            Error::BufferOverflow => write!(
+                f,
+                "unescaped string is longer than {} bytes",
+                UNESCAPE_BUFFER_SIZE
+            ),
+            Error::UnknownVariant => f.write_str("unknown variant"),
+            Error::Unsupported => f.write_str("type is not supported without allocations"),
+            Error::Custom => f.write_str("deserialization failed"),
+        }
+    }
+}
+
+impl de::StdError for Error {}
+
+impl de::Error for Error {
+    fn custom<T>(_msg: T) -> Self
+    where
+        T: fmt::Display,
+    {
+        Error::Custom
+    }
+
+    fn invalid_type(_unexp: de::Unexpected, _exp: &dyn de::Expected) -> Self {
+        Error::InvalidValue
+    }
+
+    fn invalid_value(_unexp: de::Unexpected, _exp: &dyn de::Expected) -> Self {
+        Error::InvalidValue
+    }
+
+    fn invalid_length(_len: usize, _exp: &dyn de::Expected) -> Self {
+        Error::InvalidValue
+    }
+
+    fn unknown_variant(_variant: &str, _expected: &'static [&'static str]) -> Self {
+        Error::UnknownVariant
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Error::MissingField(field)
+    }
+}
+
+impl From<EscapeError> for Error {
+    fn from(_: EscapeError) -> Self {
+        Error::InvalidEscape
+    }
+}
+
+/// Deserializer over borrowed env vars that never allocates.
+///
+/// Names of fields are compared with env var names ignoring case by default.
+/// Later vars override earlier ones with the same name.
+#[derive(Clone, Copy, Debug)]
+pub struct Deserializer<'de> {
+    vars: &'de [(&'de str, &'de str)],
+    key_case: KeyCase,
+    nesting_separator: &'static str,
+}
+
+impl<'de> Deserializer<'de> {
+    pub const fn from_vars(vars: &'de [(&'de str, &'de str)]) -> Self {
+        Deserializer {
+            vars,
+            key_case: KeyCase::Insensitive,
+            nesting_separator: "_",
+        }
+    }
+
+    /// Sets policy for matching names of fields with env var names.
+    pub const fn key_case(mut self, key_case: KeyCase) -> Self {
+        self.key_case = key_case;
+        self
+    }
+
+    /// Sets separator between names of nested fields
+    /// in env var names. Default is `_`.
+    pub const fn nesting_separator(mut self, separator: &'static str) -> Self {
+        self.nesting_separator = separator;
+        self
+    }
+
+    /// Strips names of fields in `scope` from the start of `key`.
+    fn strip_scope<'k>(&self, key: &'k str, scope: &Scope<'_>) -> Option<&'k str> {
+        let rest = match scope.parent {
+            Some(parent) => self
+                .key_case
+                .strip_separator(self.strip_scope(key, parent)?, self.nesting_separator)?,
+            None => key,
+        };
+        self.key_case.strip_prefix(rest, scope.field)
+    }
+
+    /// Returns value of the var for the field.
+    fn value(&self, scope: &Scope<'_>) -> Option<&'de str> {
+        self.vars
+            .iter()
+            .rev()
+            .find(|(key, _)| self.strip_scope(key, scope) == Some(""))
+            .map(|(_, value)| *value)
+    }
+
+    /// Checks if the field has a value or nested vars.
+    fn contains(&self, scope: &Scope<'_>) -> bool {
+        self.vars.iter().any(|(key, _)| {
+            self.strip_scope(key, scope).is_some_and(|rest| {
+                rest.is_empty()
+                    || self
+                        .key_case
+                        .strip_separator(rest, self.nesting_separator)
+                        .is_some()
+            })
+        })
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(Struct {
+            de: self,
+            scope: None,
+            fields,
+            next: 0,
+        })
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf
+        unit unit_struct seq tuple tuple_struct map enum identifier
+    }
+}
+
+/// Field of a struct and fields of its parent structs.
+struct Scope<'s> {
+    parent: Option<&'s Scope<'s>>,
+    field: &'static str,
+}
+
+/// Fields of a struct that have env vars.
+struct Struct<'s, 'de> {
+    de: Deserializer<'de>,
+    scope: Option<&'s Scope<'s>>,
+    fields: &'static [&'static str],
+
+    /// Index of the field after the current one.
+    next: usize,
+}
+
+impl<'de> de::MapAccess<'de> for Struct<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, Error>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        while let Some(&field) = self.fields.get(self.next) {
+            self.next += 1;
+
+            let scope = Scope {
+                parent: self.scope,
+                field,
+            };
+            if self.de.contains(&scope) {
+                return seed
+                    .deserialize(BorrowedStrDeserializer::new(field))
+                    .map(Some);
+            }
+        }
+        Ok(None)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let Some(field) = self.next.checked_sub(1).map(|index| self.fields[index]) else {
+            panic!("next_value called before next_key");
+        };
+
+        seed.deserialize(Field {
+            de: self.de,
+            scope: Scope {
+                parent: self.scope,
+                field,
+            },
+        })
+    }
+}
+
+/// Deserializer for a field that is either a value or a nested struct.
+struct Field<'s, 'de> {
+    de: Deserializer<'de>,
+    scope: Scope<'s>,
+}
+
+impl<'de> Field<'_, 'de> {
+    fn value(&self) -> Result<Value<'de>, Error> {
+        match self.de.value(&self.scope) {
+            Some(value) => Ok(Value(value)),
+            None => Err(Error::MissingField(self.scope.field)),
+        }
+    }
+}
+
+macro_rules! forward_to_value {
+    ($($deserialize:ident)*) => {$(
+        fn $deserialize<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.value()?.$deserialize(visitor)
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for Field<'_, 'de> {
+    type Error = Error;
+
+    forward_to_value! {
+        deserialize_any deserialize_bool
+        deserialize_i8 deserialize_i16 deserialize_i32 deserialize_i64 deserialize_i128
+        deserialize_u8 deserialize_u16 deserialize_u32 deserialize_u64 deserialize_u128
+        deserialize_f32 deserialize_f64 deserialize_char deserialize_str deserialize_string
+        deserialize_bytes deserialize_byte_buf deserialize_unit deserialize_seq deserialize_map
+        deserialize_identifier
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_map(Struct {
+            de: self.de,
+            scope: Some(&self.scope),
+            fields,
+            next: 0,
+        })
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if self.de.contains(&self.scope) {
+            visitor.visit_some(self)
+        } else {
+            visitor.visit_none()
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_unit_struct<V>(self, name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value()?.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value()?.deserialize_tuple(len, visitor)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value()?.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.value()?.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+/// Deserializer for a single value.
+#[derive(Clone, Copy)]
+struct Value<'de>(&'de str);
+
+macro_rules! parse_num {
+    ($($deserialize:ident $visit:ident)*) => {$(
+        fn $deserialize<V>(self, visitor: V) -> Result<V::Value, Error>
+        where
+            V: Visitor<'de>,
+        {
+            match self.0.trim().parse() {
+                Ok(value) => visitor.$visit(value),
+                Err(_) => Err(Error::InvalidValue),
+            }
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for Value<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bool<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let value = self.0.trim();
+        let is = |values: &[&str]| values.iter().any(|v| v.eq_ignore_ascii_case(value));
+
+        if is(&["true", "1", "+", "y", "yea", "yes", "yeah", "yah", "aye"]) {
+            visitor.visit_bool(true)
+        } else if is(&["false", "0", "-", "n", "nay", "no", "nah"]) {
+            visitor.visit_bool(false)
+        } else {
+            Err(Error::InvalidValue)
+        }
+    }
+
+    parse_num! {
+        deserialize_i8 visit_i8
+        deserialize_i16 visit_i16
+        deserialize_i32 visit_i32
+        deserialize_i64 visit_i64
+        deserialize_i128 visit_i128
+        deserialize_u8 visit_u8
+        deserialize_u16 visit_u16
+        deserialize_u32 visit_u32
+        deserialize_u64 visit_u64
+        deserialize_u128 visit_u128
+        deserialize_f32 visit_f32
+        deserialize_f64 visit_f64
+    }
+
+    fn deserialize_char<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match self.0.chars().next() {
+            Some(c) => visitor.visit_char(c),
+            None => Err(Error::InvalidValue),
+        }
+    }
+
+    fn deserialize_str<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        let Some(quoted) = self.0.strip_prefix('"') else {
+            return visitor.visit_borrowed_str(self.0);
+        };
+
+        let mut buffer = [0; UNESCAPE_BUFFER_SIZE];
+        let mut len = 0;
+        let tail = unescape_chars(quoted, '"', |c| {
+            let end = len + c.len_utf8();
+            let Some(bytes) = buffer.get_mut(len..end) else {
+                return Err(Error::BufferOverflow);
+            };
+            c.encode_utf8(bytes);
+            len = end;
+            Ok(())
+        })?;
+
+        if !tail.is_some_and(|tail| tail.trim().is_empty()) {
+            return Err(Error::InvalidEscape);
+        }
+
+        match core::str::from_utf8(&buffer[..len]) {
+            Ok(unescaped) => visitor.visit_str(unescaped),
+            Err(_) => Err(Error::InvalidEscape),
+        }
+    }
+
+    fn deserialize_string<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_identifier<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_borrowed_bytes(self.0.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_enum(Value(self.0.trim()))
+    }
+
+    fn deserialize_seq<V>(self, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+
+    fn deserialize_tuple<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+
+    fn deserialize_tuple_struct<V>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        _visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+
+    fn deserialize_map<V>(self, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+
+    fn deserialize_struct<V>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        visitor.visit_unit()
+    }
+}
+
+impl<'de> de::EnumAccess<'de> for Value<'de> {
+    type Error = Error;
+    type Variant = Self;
+
+    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self), Error>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        Ok((seed.deserialize(self)?, self))
+    }
+}
+
+/// Only unit variants are supported, as value holds just the name of variant.
+impl<'de> de::VariantAccess<'de> for Value<'de> {
+    type Error = Error;
+
+    fn unit_variant(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn newtype_variant_seed<T>(self, _seed: T) -> Result<T::Value, Error>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+
+    fn tuple_variant<V>(self, _len: usize, _visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+
+    fn struct_variant<V>(
+        self,
+        _fields: &'static [&'static str],
+        _visitor: V,
+    ) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        Err(Error::Unsupported)
+    }
+}
+
+#[test]
+fn test_heapless() {
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, serde_derive::Deserialize)]
+    enum Mode {
+        Fast,
+        Slow,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Wifi<'a> {
+        ssid: &'a str,
+        channel: Option<u8>,
+    }
+
+    #[derive(Debug, serde_derive::Deserialize)]
+    struct Config<'a> {
+        #[serde(borrow)]
+        wifi: Wifi<'a>,
+        mode: Mode,
+        debug: bool,
+        retries: Option<u32>,
+    }
+
+    static VARS: [(&str, &str); 4] = [
+        ("WIFI_SSID", "home"),
+        ("wifi_channel", "6"),
+        ("Mode", "\"Sl\\x6fw\""),
+        ("DEBUG", "yes"),
+    ];
+
+    let config = Config::deserialize(Deserializer::from_vars(&VARS)).unwrap();
+    assert_eq!(config.wifi.ssid, "home");
+    assert_eq!(config.wifi.channel, Some(6));
+    assert_eq!(config.mode, Mode::Slow);
+    assert!(config.debug);
+    assert_eq!(config.retries, None);
+
+    let err = Config::deserialize(Deserializer::from_vars(&VARS[1..])).unwrap_err();
+    assert_eq!(err, Error::MissingField("ssid"));
+
+    let vars = [("wifi-ssid", "home"), ("MODE", "Fast"), ("DEBUG", "no")];
+    let de = Deserializer::from_vars(&vars).key_case(KeyCase::Normalized);
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.wifi.ssid, "home");
+    assert_eq!(config.mode, Mode::Fast);
+}
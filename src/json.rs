@@ -1,6 +1,6 @@
 use serde::de::Visitor;
 
-use crate::{parser::Parser, Error, Options};
+use crate::{parser::Parser, Error, ErrorKind, Options};
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct JsonParser;
@@ -17,7 +17,7 @@ macro_rules! impl_parse {
                     &mut serde_json::de::Deserializer::from_reader(value.as_bytes()),
                     visitor,
                 )
-                .map_err(serde::de::Error::custom)
+                .map_err(|err| Error::with_source(ErrorKind::Json, err))
             }
         )*
     };
@@ -60,7 +60,7 @@ impl Parser for JsonParser {
             variants,
             visitor,
         )
-        .map_err(serde::de::Error::custom)
+        .map_err(|err| Error::with_source(ErrorKind::Json, err))
     }
 
     fn parse_struct<'de, V>(
@@ -79,15 +79,12 @@ impl Parser for JsonParser {
             fields,
             visitor,
         )
-        .map_err(serde::de::Error::custom)
+        .map_err(|err| Error::with_source(ErrorKind::Json, err))
     }
 }
 
 impl Options<JsonParser> {
     pub const fn json() -> Self {
-        Options {
-            parser: JsonParser,
-            ident_upper: true,
-        }
+        Options::new(JsonParser)
     }
 }
@@ -0,0 +1,155 @@
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+
+/// Policy for matching field names with env var names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum KeyCase {
+    /// Field names are compared as is.
+    Exact,
+
+    /// Uppercase field names are compared, e.g. `pool_size` matches `POOL_SIZE`.
+    #[default]
+    Upper,
+
+    /// Field names are converted to SCREAMING_SNAKE_CASE,
+    /// so `poolSize` and `pool_size` both match `POOL_SIZE`.
+    ScreamingSnake,
+
+    /// Field names are compared ignoring case.
+    Insensitive,
+
+    /// Field names are compared ignoring case,
+    /// with `-` and `.` treated as `_` on both sides,
+    /// so `pool-size` and `POOL.SIZE` match `pool_size`.
+    Normalized,
+}
+
+impl KeyCase {
+    /// Strips env var name of a field from the start of `key`.
+    pub(crate) fn strip_prefix<'k>(self, key: &'k str, name: &str) -> Option<&'k str> {
+        let mut rest = key.chars();
+        let mut matches =
+            |expected: char| rest.next().is_some_and(|actual| self.eq(expected, actual));
+
+        let matched = match self {
+            KeyCase::Exact | KeyCase::Insensitive | KeyCase::Normalized => {
+                name.chars().all(&mut matches)
+            }
+            KeyCase::Upper => name.chars().flat_map(char::to_uppercase).all(&mut matches),
+            KeyCase::ScreamingSnake => {
+                let mut previous = None;
+                name.chars().all(|c| {
+                    let boundary = is_word_boundary(previous, c);
+                    previous = Some(c);
+                    (!boundary || matches('_')) && c.to_uppercase().all(&mut matches)
+                })
+            }
+        };
+
+        matched.then_some(rest.as_str())
+    }
+
+    /// Strips nesting `separator` from the start of `key`.
+    ///
+    /// With [`KeyCase::Normalized`] `-` and `.` match `_` in the separator and vice versa.
+    pub(crate) fn strip_separator<'k>(self, key: &'k str, separator: &str) -> Option<&'k str> {
+        match self {
+            KeyCase::Normalized => self.strip_prefix(key, separator),
+            _ => key.strip_prefix(separator),
+        }
+    }
+
+    /// Splits `key` at the first nesting `separator`.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn split_separator<'k>(
+        self,
+        key: &'k str,
+        separator: &str,
+    ) -> Option<(&'k str, &'k str)> {
+        match self {
+            KeyCase::Normalized => key.char_indices().find_map(|(index, _)| {
+                let rest = self.strip_separator(&key[index..], separator)?;
+                Some((&key[..index], rest))
+            }),
+            _ => key.split_once(separator),
+        }
+    }
+
+    /// Returns env var name for a field.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn var_name(self, name: &str) -> String {
+        match self {
+            KeyCase::Exact | KeyCase::Insensitive => name.into(),
+            KeyCase::Upper => name.to_uppercase(),
+            KeyCase::ScreamingSnake => {
+                let mut var = String::with_capacity(name.len());
+                let mut previous = None;
+                for c in name.chars() {
+                    if is_word_boundary(previous, c) {
+                        var.push('_');
+                    }
+                    previous = Some(c);
+                    var.extend(c.to_uppercase());
+                }
+                var
+            }
+            KeyCase::Normalized => name
+                .chars()
+                .map(normalize)
+                .flat_map(char::to_uppercase)
+                .collect(),
+        }
+    }
+
+    fn eq(self, expected: char, actual: char) -> bool {
+        match self {
+            KeyCase::Exact | KeyCase::Upper | KeyCase::ScreamingSnake => expected == actual,
+            KeyCase::Insensitive => expected.to_lowercase().eq(actual.to_lowercase()),
+            KeyCase::Normalized => normalize(expected)
+                .to_lowercase()
+                .eq(normalize(actual).to_lowercase()),
+        }
+    }
+}
+
+/// Checks if uppercase `c` starts a new word in camelCase name.
+fn is_word_boundary(previous: Option<char>, c: char) -> bool {
+    c.is_uppercase() && previous.is_some_and(|p| p.is_lowercase() || p.is_numeric())
+}
+
+fn normalize(c: char) -> char {
+    match c {
+        '-' | '.' => '_',
+        c => c,
+    }
+}
+
+/// Transform applied to map keys taken from env var names.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MapKeys {
+    /// Keys are kept as is, e.g. `LABELS_TEAM_NAME` gives `TEAM_NAME` key.
+    #[default]
+    Keep,
+
+    /// Keys are lowercased, e.g. `team_name`.
+    Lower,
+
+    /// Keys are lowercased with separators and `_` replaced by `-`, e.g. `team-name`.
+    Kebab,
+
+    /// Keys are lowercased with separators replaced by `.`, e.g. `team.name`.
+    Dotted,
+}
+
+impl MapKeys {
+    /// Returns map key for env var name.
+    #[cfg(feature = "alloc")]
+    pub(crate) fn apply(self, key: &str, separator: &str) -> String {
+        match self {
+            MapKeys::Keep => key.into(),
+            MapKeys::Lower => key.to_lowercase(),
+            MapKeys::Kebab => key.to_lowercase().replace(separator, "-").replace('_', "-"),
+            MapKeys::Dotted => key.to_lowercase().replace(separator, "."),
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use alloc::{string::String, vec::Vec};
+
+use crate::{dotenv, Deserializer, Error};
+
+/// Builder that stacks several sources of env vars.
+///
+/// Sources added later take precedence over sources added earlier,
+/// so typical setup adds defaults first, then dotenv files,
+/// then process env vars and overrides last.
+#[derive(Debug, Default)]
+pub struct Layers {
+    vars: Vec<(String, String)>,
+}
+
+impl Layers {
+    pub fn new() -> Self {
+        Layers { vars: Vec::new() }
+    }
+
+    /// Adds explicit key-value pairs.
+    pub fn vars(mut self, vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        for (key, value) in vars {
+            match self.vars.iter().position(|(ident, _)| *ident == key) {
+                Some(index) => self.vars[index].1 = value,
+                None => self.vars.push((key, value)),
+            }
+        }
+        self
+    }
+
+    /// Adds vars parsed from contents of a dotenv file.
+    pub fn dotenv_str(self, s: &str) -> Result<Self, Error> {
+        Ok(self.vars(dotenv::parse(s)?))
+    }
+
+    /// Adds vars parsed from dotenv file at specified path.
+    #[cfg(feature = "std")]
+    pub fn dotenv_path(self, path: impl AsRef<std::path::Path>) -> Result<Self, Error> {
+        Ok(self.vars(Deserializer::from_dotenv_path(path)?.vars))
+    }
+
+    /// Adds env vars of the current process.
+    #[cfg(feature = "std")]
+    pub fn env_vars(self) -> Self {
+        self.vars(Deserializer::from_env_vars().vars)
+    }
+
+    /// Adds env vars of the current process that start with `prefix`.
+    /// Prefix is stripped from keys.
+    #[cfg(feature = "std")]
+    pub fn prefixed_env_vars(self, prefix: &str) -> Self {
+        self.vars(Deserializer::from_prefixed_env_vars(prefix).vars)
+    }
+
+    /// Builds deserializer from merged vars.
+    pub fn build(self) -> Deserializer {
+        Deserializer::from_vars(self.vars)
+    }
+}
+
+#[test]
+fn test_precedence() {
+    use alloc::borrow::ToOwned;
+    use serde::Deserialize;
+
+    #[derive(serde_derive::Deserialize)]
+    struct Config {
+        host: String,
+        port: u16,
+        debug: bool,
+    }
+
+    let de = Layers::new()
+        .vars([
+            ("HOST".to_owned(), "example.com".to_owned()),
+            ("PORT".to_owned(), "80".to_owned()),
+            ("DEBUG".to_owned(), "no".to_owned()),
+        ])
+        .dotenv_str("PORT=8080\nDEBUG=yes")
+        .unwrap()
+        .vars([("PORT".to_owned(), "9090".to_owned())])
+        .build();
+
+    let config = Config::deserialize(de).unwrap();
+    assert_eq!(config.host, "example.com");
+    assert_eq!(config.port, 9090);
+    assert!(config.debug);
+}
@@ -1,6 +1,6 @@
 use serde::de::Visitor;
 
-use crate::Error;
+use crate::{unescape::unescape_with, Error, ErrorKind};
 
 pub trait Parser: Copy {
     fn parse_bool<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
@@ -90,4 +90,88 @@ pub trait Parser: Copy {
     fn parse_any<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
     where
         V: Visitor<'de>;
+
+    /// Parses string value.
+    ///
+    /// By default unescapes value enclosed in double quotes.
+    fn parse_str<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        parse_quoted_str(value, '"', visitor)
+    }
+
+    /// Parses string value borrowed from the input.
+    ///
+    /// By default visits borrowed value if it is not enclosed in double quotes,
+    /// otherwise unescapes it with [`Parser::parse_str`].
+    /// Parsers that override `parse_str` should override this method as well.
+    fn parse_borrowed_str<'de, V>(self, value: &'de str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        if value.starts_with('"') {
+            self.parse_str(value, visitor)
+        } else {
+            visitor.visit_borrowed_str(value)
+        }
+    }
+
+    /// Parses character value.
+    ///
+    /// By default takes the first character of the value.
+    fn parse_char<'de, V>(self, value: &str, visitor: V) -> Result<V::Value, Error>
+    where
+        V: Visitor<'de>,
+    {
+        match value.chars().next() {
+            Some(c) => visitor.visit_char(c),
+            None => Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Str(value),
+                &"a character",
+            )),
+        }
+    }
+}
+
+/// Visits potentially escaped string enclosed in `quote` characters.
+pub(crate) fn parse_quoted_str<'de, V>(
+    value: &str,
+    quote: char,
+    visitor: V,
+) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    if let Some(escaped) = value.strip_prefix(quote) {
+        let (unescaped, tail) = unescape_with(escaped, quote).map_err(|_| {
+            <Error as serde::de::Error>::invalid_value(
+                serde::de::Unexpected::Str(value),
+                &"Potentially escaped string",
+            )
+            .with_kind(ErrorKind::InvalidEscape)
+        })?;
+
+        match tail {
+            None => {
+                return Err(<Error as serde::de::Error>::invalid_value(
+                    serde::de::Unexpected::Str(value),
+                    &"Escaped string with closing quote",
+                )
+                .with_kind(ErrorKind::InvalidEscape));
+            }
+            Some(tail) => {
+                let tail = tail.trim_start();
+                if !tail.is_empty() {
+                    return Err(serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Str(tail),
+                        &"Potentially escaped string without characters after closing quote",
+                    ));
+                }
+            }
+        }
+        visitor.visit_string(unescaped)
+    } else {
+        visitor.visit_str(value)
+    }
 }
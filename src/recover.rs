@@ -0,0 +1,172 @@
+//! Recovery from malformed values when errors are collected.
+
+use core::{cell::RefCell, fmt};
+
+use alloc::vec::Vec;
+
+use serde::de::{self, Visitor};
+
+use crate::Error;
+
+/// Storage for errors collected while deserialization continues.
+pub(crate) type Collected = RefCell<Vec<Error>>;
+
+/// Returns number of errors recorded so far.
+pub(crate) fn recorded(collected: Option<&Collected>) -> usize {
+    collected.map_or(0, |collected| collected.borrow().len())
+}
+
+/// Prepends env var name and field to the location of errors
+/// recorded after the first `since` ones.
+pub(crate) fn locate_recorded(
+    collected: Option<&Collected>,
+    since: usize,
+    var: &str,
+    field: &str,
+    separator: &str,
+) {
+    if let Some(collected) = collected {
+        for err in &mut collected.borrow_mut()[since..] {
+            err.locate(var, field, separator);
+        }
+    }
+}
+
+/// Visitor that keeps wrapped visitor in place until one of its methods is called.
+///
+/// If parser fails before visiting, wrapped visitor is still available
+/// and can be used to visit a fallback value.
+pub(crate) struct Slot<'a, V>(&'a mut Option<V>);
+
+impl<V> Slot<'_, V> {
+    fn take(self) -> V {
+        self.0.take().expect("visitor is already used")
+    }
+}
+
+macro_rules! forward_visit {
+    ($($visit:ident $type:ty)*) => {$(
+        fn $visit<E>(self, v: $type) -> Result<Self::Value, E>
+        where
+            E: de::Error,
+        {
+            self.take().$visit(v)
+        }
+    )*};
+}
+
+impl<'de, V> Visitor<'de> for Slot<'_, V>
+where
+    V: Visitor<'de>,
+{
+    type Value = V::Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &*self.0 {
+            Some(visitor) => visitor.expecting(f),
+            None => f.write_str("a value"),
+        }
+    }
+
+    forward_visit! {
+        visit_bool bool
+        visit_i8 i8
+        visit_i16 i16
+        visit_i32 i32
+        visit_i64 i64
+        visit_i128 i128
+        visit_u8 u8
+        visit_u16 u16
+        visit_u32 u32
+        visit_u64 u64
+        visit_u128 u128
+        visit_f32 f32
+        visit_f64 f64
+        visit_char char
+        visit_str &str
+        visit_borrowed_str &'de str
+        visit_string alloc::string::String
+        visit_bytes &[u8]
+        visit_borrowed_bytes &'de [u8]
+        visit_byte_buf Vec<u8>
+    }
+
+    fn visit_none<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.take().visit_none()
+    }
+
+    fn visit_some<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.take().visit_some(deserializer)
+    }
+
+    fn visit_unit<E>(self) -> Result<Self::Value, E>
+    where
+        E: de::Error,
+    {
+        self.take().visit_unit()
+    }
+
+    fn visit_newtype_struct<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.take().visit_newtype_struct(deserializer)
+    }
+
+    fn visit_seq<A>(self, seq: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::SeqAccess<'de>,
+    {
+        self.take().visit_seq(seq)
+    }
+
+    fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::MapAccess<'de>,
+    {
+        self.take().visit_map(map)
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<Self::Value, A::Error>
+    where
+        A: de::EnumAccess<'de>,
+    {
+        self.take().visit_enum(data)
+    }
+}
+
+/// Runs `parse` and, if it fails before using the visitor,
+/// records the error in `collected` and visits fallback value instead.
+///
+/// If errors are not collected, visitor is already used
+/// or it rejects fallback value, the error is returned.
+pub(crate) fn recover<'de, V>(
+    collected: Option<&Collected>,
+    visitor: V,
+    parse: impl FnOnce(Slot<'_, V>) -> Result<V::Value, Error>,
+    fallback: impl FnOnce(V) -> Result<V::Value, Error>,
+) -> Result<V::Value, Error>
+where
+    V: Visitor<'de>,
+{
+    let mut visitor = Some(visitor);
+    match parse(Slot(&mut visitor)) {
+        Ok(value) => Ok(value),
+        Err(err) => match (collected, visitor) {
+            (Some(collected), Some(visitor)) => match fallback(visitor) {
+                Ok(value) => {
+                    collected.borrow_mut().push(err);
+                    Ok(value)
+                }
+                Err(_) => Err(err),
+            },
+            _ => Err(err),
+        },
+    }
+}
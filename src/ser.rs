@@ -0,0 +1,944 @@
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+use base64::Engine;
+use serde::ser::{self, Serialize};
+
+use crate::Error;
+
+/// Serializes value into env var key-value pairs.
+///
+/// Mirrors naming rules of [`Deserializer`](crate::Deserializer).
+/// Fields of structs become uppercase keys, nested structs become `PARENT_CHILD` keys,
+/// sequences and maps are rendered as comma-separated values and key:value pairs
+/// understood by [`BasicParser`](crate::BasicParser).
+pub fn to_vars<T>(value: &T) -> Result<Vec<(String, String)>, Error>
+where
+    T: Serialize + ?Sized,
+{
+    let mut serializer = Serializer::new();
+    value.serialize(&mut serializer)?;
+    Ok(serializer.into_vars())
+}
+
+/// Serializer that collects env var key-value pairs.
+///
+/// Top-level value must be a struct or a map.
+#[derive(Debug, Default)]
+pub struct Serializer {
+    vars: Vec<(String, String)>,
+}
+
+impl Serializer {
+    pub fn new() -> Self {
+        Serializer { vars: Vec::new() }
+    }
+
+    /// Returns collected env var key-value pairs.
+    pub fn into_vars(self) -> Vec<(String, String)> {
+        self.vars
+    }
+}
+
+fn expected_struct_or_map() -> Error {
+    ser::Error::custom("expected a struct or a map at top level")
+}
+
+macro_rules! reject_top_level {
+    ($($serialize:ident $type:ty)*) => {$(
+        fn $serialize(self, _v: $type) -> Result<(), Error> {
+            Err(expected_struct_or_map())
+        }
+    )*};
+}
+
+impl<'a> ser::Serializer for &'a mut Serializer {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = ser::Impossible<(), Error>;
+    type SerializeTuple = ser::Impossible<(), Error>;
+    type SerializeTupleStruct = ser::Impossible<(), Error>;
+    type SerializeTupleVariant = ser::Impossible<(), Error>;
+    type SerializeMap = MapVars<'a>;
+    type SerializeStruct = StructVars<'a>;
+    type SerializeStructVariant = ser::Impossible<(), Error>;
+
+    reject_top_level! {
+        serialize_bool bool
+        serialize_i8 i8
+        serialize_i16 i16
+        serialize_i32 i32
+        serialize_i64 i64
+        serialize_i128 i128
+        serialize_u8 u8
+        serialize_u16 u16
+        serialize_u32 u32
+        serialize_u64 u64
+        serialize_u128 u128
+        serialize_f32 f32
+        serialize_f64 f64
+        serialize_char char
+        serialize_str &str
+        serialize_bytes &[u8]
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        Err(expected_struct_or_map())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Err(expected_struct_or_map())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(expected_struct_or_map())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(expected_struct_or_map())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(expected_struct_or_map())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(expected_struct_or_map())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(MapVars {
+            vars: &mut self.vars,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Ok(StructVars {
+            vars: &mut self.vars,
+            prefix: String::new(),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(expected_struct_or_map())
+    }
+}
+
+/// Pushes `value` into `vars`, replacing previous value with the same key.
+fn push_var(vars: &mut Vec<(String, String)>, key: String, value: String) {
+    match vars.iter().position(|(ident, _)| *ident == key) {
+        Some(index) => vars[index].1 = value,
+        None => vars.push((key, value)),
+    }
+}
+
+/// Collects fields of a struct as separate env vars.
+pub struct StructVars<'a> {
+    vars: &'a mut Vec<(String, String)>,
+    prefix: String,
+}
+
+impl ser::SerializeStruct for StructVars<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(VarSerializer {
+            vars: self.vars,
+            key: format!("{}{}", self.prefix, key.to_uppercase()),
+        })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Collects entries of a map as separate env vars.
+pub struct MapVars<'a> {
+    vars: &'a mut Vec<(String, String)>,
+    key: Option<String>,
+}
+
+impl ser::SerializeMap for MapVars<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.key = Some(key.serialize(ValueSerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let key = self
+            .key
+            .take()
+            .expect("serialize_value called before serialize_key");
+
+        value.serialize(VarSerializer {
+            vars: self.vars,
+            key,
+        })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serializes single env var.
+/// Structs are expanded into multiple env vars with common prefix.
+struct VarSerializer<'a> {
+    vars: &'a mut Vec<(String, String)>,
+    key: String,
+}
+
+macro_rules! serialize_var {
+    ($($serialize:ident $type:ty)*) => {$(
+        fn $serialize(self, v: $type) -> Result<(), Error> {
+            let value = ValueSerializer.$serialize(v)?;
+            push_var(self.vars, self.key, value);
+            Ok(())
+        }
+    )*};
+}
+
+impl<'a> ser::Serializer for VarSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = PushVar<'a, SeqValue>;
+    type SerializeTuple = PushVar<'a, SeqValue>;
+    type SerializeTupleStruct = PushVar<'a, SeqValue>;
+    type SerializeTupleVariant = PushVar<'a, SeqValue>;
+    type SerializeMap = PushVar<'a, MapValue>;
+    type SerializeStruct = StructVars<'a>;
+    type SerializeStructVariant = PushVar<'a, MapValue>;
+
+    serialize_var! {
+        serialize_bool bool
+        serialize_i8 i8
+        serialize_i16 i16
+        serialize_i32 i32
+        serialize_i64 i64
+        serialize_i128 i128
+        serialize_u8 u8
+        serialize_u16 u16
+        serialize_u32 u32
+        serialize_u64 u64
+        serialize_u128 u128
+        serialize_f32 f32
+        serialize_f64 f64
+        serialize_char char
+        serialize_str &str
+        serialize_bytes &[u8]
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        push_var(self.vars, self.key, String::new());
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        let value = ValueSerializer.serialize_unit_variant(name, variant_index, variant)?;
+        push_var(self.vars, self.key, value);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        let value =
+            ValueSerializer.serialize_newtype_variant(name, variant_index, variant, value)?;
+        push_var(self.vars, self.key, value);
+        Ok(())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Ok(PushVar {
+            vars: self.vars,
+            key: self.key,
+            value: ValueSerializer.serialize_seq(len)?,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Ok(PushVar {
+            vars: self.vars,
+            key: self.key,
+            value: ValueSerializer.serialize_tuple_variant(name, variant_index, variant, len)?,
+        })
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(PushVar {
+            vars: self.vars,
+            key: self.key,
+            value: ValueSerializer.serialize_map(len)?,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Ok(StructVars {
+            vars: self.vars,
+            prefix: format!("{}_", self.key),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Ok(PushVar {
+            vars: self.vars,
+            key: self.key,
+            value: ValueSerializer.serialize_struct_variant(name, variant_index, variant, len)?,
+        })
+    }
+}
+
+/// Renders compound value and pushes it as single env var.
+pub struct PushVar<'a, T> {
+    vars: &'a mut Vec<(String, String)>,
+    key: String,
+    value: T,
+}
+
+impl ser::SerializeSeq for PushVar<'_, SeqValue> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(&mut self.value, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        let PushVar { vars, key, value } = self;
+        push_var(vars, key, ser::SerializeSeq::end(value)?);
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for PushVar<'_, SeqValue> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for PushVar<'_, SeqValue> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for PushVar<'_, SeqValue> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeMap for PushVar<'_, MapValue> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeMap::serialize_key(&mut self.value, key)
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeMap::serialize_value(&mut self.value, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        let PushVar { vars, key, value } = self;
+        push_var(vars, key, ser::SerializeMap::end(value)?);
+        Ok(())
+    }
+}
+
+impl ser::SerializeStructVariant for PushVar<'_, MapValue> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeStruct::serialize_field(&mut self.value, key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// Wraps value in double quotes, escaping characters that [`unescape`](crate::unescape) understands.
+fn quote(value: &str) -> String {
+    let mut quoted = String::with_capacity(value.len() + 2);
+    quoted.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            '\0' => quoted.push_str("\\0"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Quotes element of a sequence or a map if it can't be read back verbatim.
+fn quote_element(value: String) -> String {
+    let needs_quotes = value.is_empty()
+        || value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.contains([',', ':', '"', '[', ']']);
+
+    if needs_quotes {
+        quote(&value)
+    } else {
+        value
+    }
+}
+
+/// Renders single value into a string.
+struct ValueSerializer;
+
+macro_rules! serialize_display {
+    ($($serialize:ident $type:ty)*) => {$(
+        fn $serialize(self, v: $type) -> Result<String, Error> {
+            Ok(v.to_string())
+        }
+    )*};
+}
+
+impl ser::Serializer for ValueSerializer {
+    type Ok = String;
+    type Error = Error;
+
+    type SerializeSeq = SeqValue;
+    type SerializeTuple = SeqValue;
+    type SerializeTupleStruct = SeqValue;
+    type SerializeTupleVariant = SeqValue;
+    type SerializeMap = MapValue;
+    type SerializeStruct = MapValue;
+    type SerializeStructVariant = MapValue;
+
+    serialize_display! {
+        serialize_bool bool
+        serialize_i8 i8
+        serialize_i16 i16
+        serialize_i32 i32
+        serialize_i64 i64
+        serialize_i128 i128
+        serialize_u8 u8
+        serialize_u16 u16
+        serialize_u32 u32
+        serialize_u64 u64
+        serialize_u128 u128
+        serialize_f32 f32
+        serialize_f64 f64
+        serialize_char char
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, Error> {
+        if v.starts_with('"') {
+            Ok(quote(v))
+        } else {
+            Ok(v.to_owned())
+        }
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<String, Error> {
+        Ok(base64::engine::general_purpose::STANDARD_NO_PAD.encode(v))
+    }
+
+    fn serialize_none(self) -> Result<String, Error> {
+        Err(ser::Error::custom(
+            "`None` can be serialized only as absent env var",
+        ))
+    }
+
+    fn serialize_some<T>(self, value: &T) -> Result<String, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<String, Error> {
+        Ok(String::new())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, Error> {
+        Ok(String::new())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Error> {
+        Ok(variant.to_owned())
+    }
+
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<String, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<String, Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        Ok(format!("{}:{}", variant, value.serialize(self)?))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<SeqValue, Error> {
+        Ok(SeqValue {
+            output: String::new(),
+            first: true,
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqValue, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqValue, Error> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SeqValue, Error> {
+        Ok(SeqValue {
+            output: format!("{}:", variant),
+            first: true,
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapValue, Error> {
+        Ok(MapValue {
+            output: String::new(),
+            first: true,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, len: usize) -> Result<MapValue, Error> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<MapValue, Error> {
+        Ok(MapValue {
+            output: format!("{}:", variant),
+            first: true,
+        })
+    }
+}
+
+/// Renders comma-separated values.
+pub struct SeqValue {
+    output: String,
+    first: bool,
+}
+
+impl ser::SerializeSeq for SeqValue {
+    type Ok = String;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        if !self.first {
+            self.output.push(',');
+        }
+        self.first = false;
+        self.output
+            .push_str(&quote_element(value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<String, Error> {
+        Ok(self.output)
+    }
+}
+
+impl ser::SerializeTuple for SeqValue {
+    type Ok = String;
+    type Error = Error;
+
+    fn serialize_element<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<String, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SeqValue {
+    type Ok = String;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<String, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for SeqValue {
+    type Ok = String;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<String, Error> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+/// Renders comma-separated key:value pairs.
+pub struct MapValue {
+    output: String,
+    first: bool,
+}
+
+impl ser::SerializeMap for MapValue {
+    type Ok = String;
+    type Error = Error;
+
+    fn serialize_key<T>(&mut self, key: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        if !self.first {
+            self.output.push(',');
+        }
+        self.first = false;
+        self.output
+            .push_str(&quote_element(key.serialize(ValueSerializer)?));
+        self.output.push(':');
+        Ok(())
+    }
+
+    fn serialize_value<T>(&mut self, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        self.output
+            .push_str(&quote_element(value.serialize(ValueSerializer)?));
+        Ok(())
+    }
+
+    fn end(self) -> Result<String, Error> {
+        Ok(self.output)
+    }
+}
+
+impl ser::SerializeStruct for MapValue {
+    type Ok = String;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeMap::serialize_entry(self, key, value)
+    }
+
+    fn end(self) -> Result<String, Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for MapValue {
+    type Ok = String;
+    type Error = Error;
+
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<(), Error>
+    where
+        T: Serialize + ?Sized,
+    {
+        ser::SerializeMap::serialize_entry(self, key, value)
+    }
+
+    fn end(self) -> Result<String, Error> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+#[test]
+fn test_round_trip() {
+    use alloc::{collections::BTreeMap, vec};
+    use serde::Deserialize;
+
+    #[derive(Debug, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+    enum Mode {
+        Fast,
+        Limit(u32),
+    }
+
+    #[derive(Debug, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+    struct Pool {
+        size: u32,
+        timeout: Option<f32>,
+    }
+
+    #[derive(Debug, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+    struct Database {
+        url: String,
+        pool: Pool,
+    }
+
+    #[derive(Debug, PartialEq, serde_derive::Serialize, serde_derive::Deserialize)]
+    struct Config {
+        name: String,
+        quoted: String,
+        hosts: Vec<String>,
+        brackets: Vec<String>,
+        one: Vec<String>,
+        labels: BTreeMap<String, String>,
+        modes: Vec<Mode>,
+        database: Database,
+        debug: Option<bool>,
+    }
+
+    let config = Config {
+        name: "app".to_owned(),
+        quoted: "\"quoted\"".to_owned(),
+        hosts: vec!["a,b".to_owned(), "http://c".to_owned(), "".to_owned()],
+        brackets: vec!["[a".to_owned(), "b]".to_owned(), "c[d".to_owned()],
+        one: vec!["[x]".to_owned()],
+        labels: [("team".to_owned(), "core, infra".to_owned())]
+            .into_iter()
+            .collect(),
+        modes: vec![Mode::Fast, Mode::Limit(3)],
+        database: Database {
+            url: "postgres://localhost".to_owned(),
+            pool: Pool {
+                size: 4,
+                timeout: None,
+            },
+        },
+        debug: None,
+    };
+
+    let vars = to_vars(&config).unwrap();
+    assert!(vars.contains(&("DATABASE_POOL_SIZE".to_owned(), "4".to_owned())));
+    assert!(vars.iter().all(|(key, _)| key != "DEBUG"));
+
+    let de = crate::Deserializer::from_vars(vars);
+    assert_eq!(Config::deserialize(de).unwrap(), config);
+}
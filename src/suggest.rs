@@ -0,0 +1,59 @@
+//! Suggestions of valid env var names for misspelled ones.
+
+use alloc::{string::String, vec::Vec};
+
+use crate::KeyCase;
+
+/// Finds the closest valid env var name for unknown `key`.
+///
+/// Each field name is compared with every head of the key up to a `separator`,
+/// keeping the rest of the key as is, so `DATABSE_URL` suggests `DATABASE_URL`.
+pub(crate) fn suggest(
+    key: &str,
+    fields: &[&str],
+    key_case: KeyCase,
+    separator: &str,
+) -> Option<String> {
+    let heads = key
+        .match_indices(separator)
+        .map(|(index, _)| index)
+        .chain(Some(key.len()));
+
+    heads
+        .flat_map(|split| fields.iter().map(move |field| (split, *field)))
+        .filter_map(|(split, field)| {
+            let field = key_case.var_name(field);
+            let distance = distance(&key[..split], &field);
+            let max = (field.chars().count() / 3).max(1);
+            (distance <= max).then(|| (distance, field + &key[split..]))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, suggestion)| suggestion)
+}
+
+/// Edit distance between two strings,
+/// counting transposition of adjacent characters as a single edit.
+fn distance(a: &str, b: &str) -> usize {
+    let a = a.chars().collect::<Vec<_>>();
+    let b = b.chars().collect::<Vec<_>>();
+
+    let mut before = Vec::new();
+    let mut previous = (0..=b.len()).collect::<Vec<_>>();
+
+    for i in 0..a.len() {
+        let mut current = Vec::with_capacity(b.len() + 1);
+        current.push(i + 1);
+        for j in 0..b.len() {
+            let mut edits = (previous[j] + usize::from(a[i] != b[j]))
+                .min(previous[j + 1] + 1)
+                .min(current[j] + 1);
+            if i > 0 && j > 0 && a[i] == b[j - 1] && a[i - 1] == b[j] {
+                edits = edits.min(before[j - 1] + 1);
+            }
+            current.push(edits);
+        }
+        before = core::mem::replace(&mut previous, current);
+    }
+
+    previous[b.len()]
+}
@@ -1,6 +1,6 @@
 use serde::de::Visitor;
 
-use crate::{parser::Parser, Error, Options};
+use crate::{parser::Parser, Error, ErrorKind, Options};
 
 #[derive(Clone, Copy, Debug, Default)]
 pub struct TomlParser;
@@ -17,7 +17,7 @@ macro_rules! impl_parse {
                     toml::Deserializer::new(value),
                     visitor,
                 )
-                .map_err(serde::de::Error::custom)
+                .map_err(|err| Error::with_source(ErrorKind::Toml, err))
             }
         )*
     };
@@ -60,7 +60,7 @@ impl Parser for TomlParser {
             variants,
             visitor,
         )
-        .map_err(serde::de::Error::custom)
+        .map_err(|err| Error::with_source(ErrorKind::Toml, err))
     }
 
     fn parse_struct<'de, V>(
@@ -79,15 +79,12 @@ impl Parser for TomlParser {
             fields,
             visitor,
         )
-        .map_err(serde::de::Error::custom)
+        .map_err(|err| Error::with_source(ErrorKind::Toml, err))
     }
 }
 
 impl Options<TomlParser> {
     pub const fn toml() -> Self {
-        Options {
-            parser: TomlParser,
-            ident_upper: true,
-        }
+        Options::new(TomlParser)
     }
 }
@@ -1,24 +1,51 @@
+#[cfg(feature = "alloc")]
 use alloc::{borrow::Cow, string::String};
 
 #[derive(Debug)]
 pub struct EscapeError;
 
+#[cfg(feature = "alloc")]
 pub fn unescape(s: &str) -> Result<(String, Option<&str>), EscapeError> {
-    let mut chars = s.chars();
+    unescape_with(s, '"')
+}
+
+/// Same as [`unescape`], but string is closed by `quote` character.
+#[cfg(feature = "alloc")]
+pub(crate) fn unescape_with(s: &str, quote: char) -> Result<(String, Option<&str>), EscapeError> {
     let mut result = String::new();
+    let tail = unescape_chars(s, quote, |c| {
+        result.push(c);
+        Ok::<_, EscapeError>(())
+    })?;
+    Ok((result, tail))
+}
+
+/// Passes unescaped characters of `s` to `push` until closing `quote` character.
+///
+/// Returns the rest of the string after closing quote, if any.
+pub(crate) fn unescape_chars<E>(
+    s: &str,
+    quote: char,
+    mut push: impl FnMut(char) -> Result<(), E>,
+) -> Result<Option<&str>, E>
+where
+    E: From<EscapeError>,
+{
+    let mut chars = s.chars();
     while let Some(c) = chars.next() {
         if c == '\\' {
             let Some(c) = chars.next() else {
-                return Err(EscapeError);
+                return Err(EscapeError.into());
             };
             match c {
-                'n' => result.push('\n'),
-                'r' => result.push('\r'),
-                't' => result.push('\t'),
-                '0' => result.push('\0'),
-                '\\' => result.push('\\'),
-                '"' => result.push('"'),
-                '\'' => result.push('\''),
+                'n' => push('\n')?,
+                'r' => push('\r')?,
+                't' => push('\t')?,
+                '0' => push('\0')?,
+                '\\' => push('\\')?,
+                '"' => push('"')?,
+                '\'' => push('\'')?,
+                c if c == quote => push(c)?,
                 'x' | 'u' | 'U' => {
                     let s = chars.as_str();
                     let hex = if s.starts_with('{') {
@@ -37,27 +64,29 @@ pub fn unescape(s: &str) -> Result<(String, Option<&str>), EscapeError> {
                     }
                     .ok_or(EscapeError)?;
                     if !hex.is_ascii() {
-                        return Err(EscapeError);
+                        return Err(EscapeError.into());
                     }
-                    let v = u32::from_str_radix(&hex, 16).map_err(|_| EscapeError)?;
-                    result.push(char::from_u32(v).ok_or(EscapeError)?);
+                    let v = u32::from_str_radix(hex, 16).map_err(|_| EscapeError)?;
+                    push(char::from_u32(v).ok_or(EscapeError)?)?;
                 }
-                _ => return Err(EscapeError),
+                _ => return Err(EscapeError.into()),
             }
-        } else if c == '"' {
-            return Ok((result, Some(chars.as_str())));
+        } else if c == quote {
+            return Ok(Some(chars.as_str()));
         } else {
-            result.push(c);
+            push(c)?;
         }
     }
-    Ok((result, None))
+    Ok(None)
 }
 
-pub fn unescaped(s: &str) -> Result<Cow<'_, str>, EscapeError> {
-    match s.strip_prefix('"') {
+/// Unescapes string if it is quoted with `quote` character.
+#[cfg(feature = "alloc")]
+pub(crate) fn unescaped_with(s: &str, quote: char) -> Result<Cow<'_, str>, EscapeError> {
+    match s.strip_prefix(quote) {
         None => Ok(Cow::Borrowed(s)),
         Some(s) => {
-            let (s, tail) = unescape(s)?;
+            let (s, tail) = unescape_with(s, quote)?;
             if let Some(tail) = tail {
                 if tail.is_empty() {
                     Ok(Cow::Owned(s))
@@ -71,10 +100,11 @@ pub fn unescaped(s: &str) -> Result<Cow<'_, str>, EscapeError> {
     }
 }
 
+#[cfg(feature = "alloc")]
 #[test]
 fn test_unescape() {
     assert_eq!(
         unescape(r#"\x20\u0020\U00000020\x{00020}\u{20}\U{020}"#).unwrap(),
-        ("      ".to_string(), None)
+        (String::from("      "), None)
     );
 }